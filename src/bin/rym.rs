@@ -17,7 +17,11 @@ struct Entry {
 #[derive(clap::Subcommand)]
 enum Subcommand {
     /// Download the Reveal.js resources
-    Update,
+    Update {
+        /// Offline mode, also download the MathJax archive
+        #[clap(long)]
+        offline: bool,
+    },
     /// Create a new project and its directory
     New {
         /// Project dir
@@ -46,6 +50,12 @@ enum Subcommand {
         /// Do not open the browser.
         #[clap(long)]
         no_open: bool,
+        /// Offline mode, serve MathJax from the local archive
+        #[clap(long)]
+        offline: bool,
+        /// Strict mode, report the unknown keys as warnings
+        #[clap(long)]
+        strict: bool,
     },
     /// Format the current project
     Fmt {
@@ -59,6 +69,29 @@ enum Subcommand {
         #[clap(short, long)]
         dry_run: bool,
     },
+    /// Check the current project without packing
+    Check {
+        /// Project dir
+        #[clap(default_value = ".")]
+        dir: PathBuf,
+        /// Project filename
+        #[clap(short, long, default_value = ROOT)]
+        name: String,
+        /// Output the diagnostics in JSON
+        #[clap(long)]
+        json: bool,
+        /// Strict mode, report the unknown keys as warnings
+        #[clap(long)]
+        strict: bool,
+    },
+    /// Serve the language server protocol over stdio
+    Lsp {
+        /// Strict mode, report the unknown keys as warnings
+        #[clap(long)]
+        strict: bool,
+    },
+    /// Print the JSON schema of the project files
+    Schema,
     /// Pack the current project
     Pack {
         /// Project dir
@@ -69,18 +102,29 @@ enum Subcommand {
         /// Output dir
         #[clap(short, long, default_value = "./package")]
         out: String,
+        /// Offline mode, include MathJax in the package
+        #[clap(long)]
+        offline: bool,
+        /// Strict mode, report the unknown keys as warnings
+        #[clap(long)]
+        strict: bool,
     },
 }
 
 fn main() -> Result<(), Error> {
     match Entry::parse().subcommand {
-        Subcommand::Update => update(),
+        Subcommand::Update { offline } => update(offline),
         Subcommand::New { dir } => blank(dir, true),
         Subcommand::Init { dir } => blank(dir, false),
-        Subcommand::Serve { dir, port, name, edit, no_open } => {
-            serve(port, dir, name, edit, !no_open)
+        Subcommand::Serve { dir, port, name, edit, no_open, offline, strict } => {
+            serve(port, dir, name, edit, !no_open, offline, strict)
         }
         Subcommand::Fmt { dir, name, dry_run } => fmt(dir, dry_run, &name),
-        Subcommand::Pack { dir, name, out } => pack(dir, out, &name),
+        Subcommand::Check { dir, name, json, strict } => check(dir, &name, json, strict),
+        Subcommand::Lsp { strict } => lsp(strict),
+        Subcommand::Schema => schema(),
+        Subcommand::Pack { dir, name, out, offline, strict } => {
+            pack(dir, out, &name, offline, strict)
+        }
     }
 }
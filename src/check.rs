@@ -0,0 +1,40 @@
+use crate::project::validate;
+use std::{
+    fs::read_to_string,
+    io::{Error, ErrorKind, Result},
+    path::Path,
+};
+
+/// Check the project without writing the package.
+///
+/// Report the missing local files, the duplicated slide ids, the undefined
+/// slide links, the empty slides and the unknown themes, as well as the errors
+/// of the rendering, such as the references, the citations and the invalid
+/// TeX. The JSON output is a list of the diagnostics. Return an error if there
+/// are any errors.
+pub fn check<P>(path: P, project: &str, json: bool, strict: bool) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    std::env::set_current_dir(path.as_ref())?;
+    let diags = validate(
+        &read_to_string(project)?,
+        Path::new(project),
+        strict,
+        &mut Vec::new(),
+    );
+    let errors = diags.errors();
+    if json {
+        println!("{}", diags.to_json());
+    } else {
+        diags.print();
+        let warnings = diags.0.len() - errors;
+        println!("{errors} error(s), {warnings} warning(s)");
+    }
+    if errors == 0 {
+        Ok(())
+    } else {
+        let msg = format!("{errors} error(s) found");
+        Err(Error::new(ErrorKind::InvalidData, msg))
+    }
+}
@@ -2,15 +2,21 @@
 #![warn(missing_docs)]
 pub use crate::{
     blank::{blank, ROOT},
+    check::check,
     fmt::fmt,
+    lsp::lsp,
     pack::pack,
+    schema::schema,
     serve::serve,
     update::update,
 };
 
 mod blank;
+mod check;
 mod fmt;
+mod lsp;
 mod pack;
 pub mod project;
+mod schema;
 mod serve;
 mod update;
@@ -0,0 +1,448 @@
+use self::{
+    cursor::{byte_index, in_link, link_at, utf16, Cursor},
+    fields::Fields,
+};
+use crate::{
+    blank::ROOT,
+    project::{archive_files, validate, Diagnostic, Symbol, Symbols},
+};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as _, PublishDiagnostics,
+    },
+    request::{Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, Request as _},
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, DiagnosticSeverity,
+    DocumentSymbol, DocumentSymbolParams, Documentation, GotoDefinitionParams, Hover,
+    HoverContents, HoverParams, Location, MarkupContent, MarkupKind, OneOf, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, SymbolKind, TextDocumentSyncKind, Url,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::read_to_string,
+    io::{Error, Result},
+    path::{Path, PathBuf},
+};
+
+mod cursor;
+mod fields;
+
+/// The Reveal.js transitions.
+const TRANSITIONS: &[&str] = &["none", "fade", "slide", "convex", "concave", "zoom"];
+/// The content blocks with the fragment option.
+const FRAG_TAGS: &[&str] = &[
+    "fit", "doc", "include", "math", "code", "table", "chart", "dot",
+];
+/// The Reveal.js fragment styles.
+const FRAG_STYLES: &[&str] = &[
+    "fade-out",
+    "fade-up",
+    "fade-down",
+    "fade-left",
+    "fade-right",
+    "fade-in-then-out",
+    "fade-in-then-semi-out",
+    "grow",
+    "shrink",
+    "strike",
+    "highlight-red",
+    "highlight-green",
+    "highlight-blue",
+    "highlight-current-red",
+    "highlight-current-green",
+    "highlight-current-blue",
+    "semi-fade-out",
+    "current-visible",
+];
+
+/// Parse the request parameters and reply.
+fn reply<P, R>(Request { id, params, .. }: Request, f: impl FnOnce(P) -> R) -> Response
+where
+    P: DeserializeOwned,
+    R: Serialize,
+{
+    match serde_json::from_value(params) {
+        Ok(params) => Response::new_ok(id, f(params)),
+        Err(e) => Response::new_err(id, ErrorCode::InvalidParams as i32, e.to_string()),
+    }
+}
+
+fn markdown(value: String) -> MarkupContent {
+    MarkupContent { kind: MarkupKind::Markdown, value }
+}
+
+/// The project directory of the file, the nearest directory with the project
+/// file, or the parent directory.
+fn project_dir(path: &Path) -> &Path {
+    let parent = path.parent().unwrap_or_else(|| Path::new("/"));
+    parent
+        .ancestors()
+        .find(|d| d.join(ROOT).is_file())
+        .unwrap_or(parent)
+}
+
+struct Server {
+    conn: Connection,
+    docs: HashMap<Url, String>,
+    /// The files with the diagnostics of each project file.
+    published: HashMap<PathBuf, HashSet<Url>>,
+    fields: Fields,
+    strict: bool,
+}
+
+impl Server {
+    fn run(&mut self) -> Result<()> {
+        while let Ok(msg) = self.conn.receiver.recv() {
+            match msg {
+                Message::Request(req) => {
+                    if self.conn.handle_shutdown(&req).map_err(Error::other)? {
+                        return Ok(());
+                    }
+                    let res = self.request(req);
+                    self.conn.sender.send(res.into()).map_err(Error::other)?;
+                }
+                Message::Notification(n) => {
+                    let method = n.method.clone();
+                    if let Err(e) = self.notify(n) {
+                        eprintln!("failed to handle `{method}`: {e}");
+                    }
+                }
+                Message::Response(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn request(&self, req: Request) -> Response {
+        match req.method.as_str() {
+            Completion::METHOD => reply(req, |p| self.completion(p)),
+            HoverRequest::METHOD => reply(req, |p| self.hover(p)),
+            GotoDefinition::METHOD => reply(req, |p| self.definition(p)),
+            DocumentSymbolRequest::METHOD => reply(req, |p| self.symbols(p)),
+            _ => {
+                let msg = format!("unsupported method `{}`", req.method);
+                Response::new_err(req.id, ErrorCode::MethodNotFound as i32, msg)
+            }
+        }
+    }
+
+    fn notify(&mut self, n: Notification) -> Result<()> {
+        let params = n.params;
+        match n.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let p = serde_json::from_value::<lsp_types::DidOpenTextDocumentParams>(params)?;
+                self.docs
+                    .insert(p.text_document.uri.clone(), p.text_document.text);
+                self.publish(&p.text_document.uri, &mut HashSet::new())
+            }
+            DidChangeTextDocument::METHOD => {
+                let p = serde_json::from_value::<lsp_types::DidChangeTextDocumentParams>(params)?;
+                if let Some(change) = p.content_changes.into_iter().last() {
+                    self.docs.insert(p.text_document.uri.clone(), change.text);
+                }
+                self.publish(&p.text_document.uri, &mut HashSet::new())
+            }
+            // The included files may be changed
+            DidSaveTextDocument::METHOD => {
+                let uris = self.docs.keys().cloned().collect::<Vec<_>>();
+                let mut done = HashSet::new();
+                uris.iter().try_for_each(|uri| self.publish(uri, &mut done))
+            }
+            DidCloseTextDocument::METHOD => {
+                let p = serde_json::from_value::<lsp_types::DidCloseTextDocumentParams>(params)?;
+                self.docs.remove(&p.text_document.uri);
+                self.send(p.text_document.uri, Vec::new())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn send(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+        let n = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.conn.sender.send(n.into()).map_err(Error::other)
+    }
+
+    /// The document and its path, the non-file documents are skipped.
+    fn doc(&self, uri: &Url) -> Option<(&str, PathBuf)> {
+        Some((self.docs.get(uri)?, uri.to_file_path().ok()?))
+    }
+
+    /// Validate the project of the document, or the document itself if it is
+    /// not a part of the project. The validated files are skipped.
+    fn publish(&mut self, uri: &Url, done: &mut HashSet<PathBuf>) -> Result<()> {
+        let Some((_, path)) = self.doc(uri) else {
+            return Ok(());
+        };
+        let root = project_dir(&path).join(ROOT);
+        let canonical = |p: &Path| p.canonicalize().unwrap_or(p.into());
+        if root.is_file() {
+            if done.insert(root.clone()) {
+                let files = self.publish_root(&root)?;
+                done.extend(files.iter().map(|p| canonical(p)));
+            }
+            if done.contains(&canonical(&path)) {
+                return Ok(());
+            }
+        }
+        if done.insert(path.clone()) {
+            self.publish_root(&path)?;
+        }
+        Ok(())
+    }
+
+    /// Validate the project and publish the diagnostics of each file, the
+    /// files without the diagnostics are cleared. Return the loaded files.
+    fn publish_root(&mut self, root: &Path) -> Result<Vec<PathBuf>> {
+        let root_uri = Url::from_file_path(root).map_err(|_| Error::other("invalid path"))?;
+        let doc = match self.docs.get(&root_uri) {
+            Some(doc) => doc.clone(),
+            None => read_to_string(root)?,
+        };
+        let mut files = HashMap::<Url, Vec<lsp_types::Diagnostic>>::new();
+        files.insert(root_uri.clone(), Vec::new());
+        let mut loaded = Vec::new();
+        for d in validate(&doc, root, self.strict, &mut loaded).0 {
+            let mut message = d.msg.clone();
+            if !d.hint.is_empty() {
+                message += &format!("\nhint: {}", d.hint);
+            }
+            // The diagnostics without the location are shown at the top of
+            // the project file
+            let file = Path::new(&d.file);
+            let (uri, range) = if d.file.is_empty() {
+                (root_uri.clone(), Range::default())
+            } else if file == root {
+                (root_uri.clone(), range(&d))
+            } else {
+                let file = file.canonicalize().unwrap_or(file.into());
+                match Url::from_file_path(file) {
+                    Ok(uri) => (uri, range(&d)),
+                    Err(()) => (root_uri.clone(), Range::default()),
+                }
+            };
+            let severity = if d.warning {
+                DiagnosticSeverity::WARNING
+            } else {
+                DiagnosticSeverity::ERROR
+            };
+            let diag = lsp_types::Diagnostic {
+                range,
+                severity: Some(severity),
+                source: Some("rym".to_string()),
+                message,
+                ..Default::default()
+            };
+            files.entry(uri).or_default().push(diag);
+        }
+        let last = self
+            .published
+            .insert(root.to_path_buf(), files.keys().cloned().collect())
+            .unwrap_or_default();
+        for uri in last {
+            files.entry(uri).or_default();
+        }
+        files
+            .into_iter()
+            .try_for_each(|(uri, diags)| self.send(uri, diags))?;
+        Ok(loaded)
+    }
+
+    fn completion(&self, p: CompletionParams) -> Option<Vec<CompletionItem>> {
+        let uri = &p.text_document_position.text_document.uri;
+        let pos = p.text_document_position.position;
+        let (doc, path) = self.doc(uri)?;
+        let lines = doc.lines().collect::<Vec<_>>();
+        let line = lines.get(pos.line as usize).copied().unwrap_or_default();
+        let col = byte_index(line, pos.character);
+        let item = |label: &str, kind, doc: String| CompletionItem {
+            label: label.to_string(),
+            kind: Some(kind),
+            documentation: (!doc.is_empty()).then(|| Documentation::MarkupContent(markdown(doc))),
+            ..Default::default()
+        };
+        if in_link(line, col) {
+            let symbols = Symbols::new(doc, &path).ok()?;
+            let items = symbols
+                .ids()
+                .map(|(id, name)| item(id, CompletionItemKind::REFERENCE, name.to_string()))
+                .collect();
+            return Some(items);
+        }
+        let cursor = Cursor::new(&lines, pos.line as usize, col)?;
+        let path = cursor.path.as_slice();
+        let values = |values: &[&str]| {
+            values
+                .iter()
+                .map(|v| item(v, CompletionItemKind::ENUM_MEMBER, String::new()))
+                .collect()
+        };
+        let items = match (path.last().copied(), cursor.key) {
+            (Some("frag"), None) => values(FRAG_TAGS),
+            (Some("frag"), Some(_)) => values(FRAG_STYLES),
+            (_, Some(key @ ("theme" | "code-theme"))) => {
+                let dir = if key == "theme" {
+                    "dist/theme/"
+                } else {
+                    "plugin/highlight/"
+                };
+                let mut themes = archive_files()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|f| Some(f.strip_prefix(dir)?.strip_suffix(".css")?.to_string()))
+                    .filter(|f| !f.contains('/'))
+                    .collect::<Vec<_>>();
+                themes.sort_unstable();
+                themes
+                    .iter()
+                    .map(|v| item(v, CompletionItemKind::ENUM_MEMBER, String::new()))
+                    .collect()
+            }
+            (_, Some("trans" | "bg-trans")) => values(TRANSITIONS),
+            (_, Some(key)) => self
+                .fields
+                .values(path, key)
+                .into_iter()
+                .map(|(v, doc)| item(&v, CompletionItemKind::ENUM_MEMBER, doc))
+                .collect(),
+            (_, None) => self
+                .fields
+                .keys(path)
+                .into_iter()
+                .map(|(key, doc)| CompletionItem {
+                    insert_text: Some(format!("{key}: ")),
+                    ..item(key, CompletionItemKind::PROPERTY, doc)
+                })
+                .collect(),
+        };
+        Some(items)
+    }
+
+    fn hover(&self, p: HoverParams) -> Option<Hover> {
+        let uri = &p.text_document_position_params.text_document.uri;
+        let pos = p.text_document_position_params.position;
+        let (doc, _) = self.doc(uri)?;
+        let lines = doc.lines().collect::<Vec<_>>();
+        let line = lines.get(pos.line as usize)?;
+        let col = byte_index(line, pos.character);
+        let cursor = Cursor::new(&lines, pos.line as usize, col)?;
+        let (start, end) = cursor.span;
+        if !(start..end).contains(&col) {
+            return None;
+        }
+        let doc = self.fields.doc(&cursor.path, &line[start..end])?;
+        let range = Range::new(
+            Position::new(pos.line, utf16(line, start)),
+            Position::new(pos.line, utf16(line, end)),
+        );
+        Some(Hover {
+            contents: HoverContents::Markup(markdown(doc)),
+            range: Some(range),
+        })
+    }
+
+    fn definition(&self, p: GotoDefinitionParams) -> Option<Location> {
+        let uri = &p.text_document_position_params.text_document.uri;
+        let pos = p.text_document_position_params.position;
+        let (doc, path) = self.doc(uri)?;
+        let line = doc.lines().nth(pos.line as usize)?;
+        let link = link_at(line, byte_index(line, pos.character))?;
+        let symbols = Symbols::new(doc, &path).ok()?;
+        let d = symbols.find(link)?;
+        let uri = Url::from_file_path(Path::new(&d.file).canonicalize().ok()?).ok()?;
+        Some(Location::new(uri, range(d)))
+    }
+
+    fn symbols(&self, p: DocumentSymbolParams) -> Option<Vec<DocumentSymbol>> {
+        let (doc, path) = self.doc(&p.text_document.uri)?;
+        let symbols = Symbols::new(doc, &path).ok()?;
+        let file = path.display().to_string();
+        let end = Position::new(doc.lines().count() as u32, 0);
+        Some(outline(&symbols.slides, &file, end, SymbolKind::MODULE))
+    }
+}
+
+/// The range of the token at the diagnostic.
+fn range(d: &Diagnostic) -> Range {
+    let line = &d.excerpt;
+    let start = line
+        .char_indices()
+        .nth(d.column.saturating_sub(1))
+        .map_or(line.len(), |(i, _)| i);
+    let len = line[start..]
+        .find(|c: char| c.is_whitespace() || matches!(c, ',' | ']' | '}'))
+        .unwrap_or(line.len() - start);
+    let row = d.line.saturating_sub(1) as u32;
+    Range::new(
+        Position::new(row, utf16(line, start)),
+        Position::new(row, utf16(line, start + len.max(1))),
+    )
+}
+
+/// The document outline of the slides in the file, each slide ends before
+/// the next one.
+#[allow(deprecated)]
+fn outline(slides: &[Symbol], file: &str, end: Position, kind: SymbolKind) -> Vec<DocumentSymbol> {
+    let slides = slides
+        .iter()
+        .filter(|s| s.loc.file == file)
+        .collect::<Vec<_>>();
+    let starts = slides
+        .iter()
+        .map(|s| range(&s.loc).start)
+        .collect::<Vec<_>>();
+    slides
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let start = starts[i];
+            let end = starts.get(i + 1).copied().unwrap_or(end);
+            let selection = Range::new(start, range(&s.loc).end.min(end));
+            DocumentSymbol {
+                name: s.name.clone(),
+                detail: None,
+                kind,
+                tags: None,
+                deprecated: None,
+                range: Range::new(start, end),
+                selection_range: selection,
+                children: Some(outline(&s.sub, file, end, SymbolKind::OBJECT)),
+            }
+        })
+        .collect()
+}
+
+/// Serve the language server protocol over stdio.
+///
+/// The diagnostics, the completion of the keys and values, the hover
+/// documents, the definitions of the slide links and the document outline
+/// are supported. The project is checked from its project file, so the
+/// diagnostics of the included files are updated as they are saved.
+pub fn lsp(strict: bool) -> Result<()> {
+    let (conn, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncKind::FULL.into()),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".into(), "/".into(), "[".into()]),
+            ..Default::default()
+        }),
+        hover_provider: Some(true.into()),
+        definition_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    conn.initialize(serde_json::to_value(capabilities)?)
+        .map_err(Error::other)?;
+    let mut server = Server {
+        conn,
+        docs: HashMap::new(),
+        published: HashMap::new(),
+        fields: Fields::new(),
+        strict,
+    };
+    server.run()?;
+    drop(server);
+    io_threads.join()
+}
@@ -0,0 +1,199 @@
+use crate::project::slide_link;
+
+/// A line of the YAML source, the flow collections are not parsed.
+struct Line<'a> {
+    /// The column of the sequence item or the key.
+    indent: usize,
+    /// The column of the key, after the `- ` markers.
+    column: usize,
+    key: Option<&'a str>,
+    value: &'a str,
+}
+
+impl<'a> Line<'a> {
+    fn new(line: &'a str) -> Self {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let mut rest = &line[indent..];
+        while let Some(s) = rest
+            .strip_prefix("- ")
+            .or_else(|| rest.strip_prefix('-').filter(|s| s.is_empty()))
+        {
+            rest = s.trim_start_matches(' ');
+        }
+        let column = line.len() - rest.len();
+        let (key, value) = match rest
+            .find(": ")
+            .or_else(|| rest.strip_suffix(':').map(str::len))
+        {
+            Some(i) if !rest.starts_with(['"', '\'', '[', '{', '#']) => {
+                (Some(&rest[..i]), rest[i + 1..].trim())
+            }
+            _ => (None, rest),
+        };
+        Self { indent, column, key, value }
+    }
+
+    fn is_blank(&self) -> bool {
+        self.key.is_none() && (self.value.is_empty() || self.value.starts_with('#'))
+    }
+
+    /// The column of the parent node if the line starts a block scalar, such
+    /// as `key: |` and `- >-`.
+    fn block(&self) -> Option<usize> {
+        let header = self.value.split(" #").next().unwrap_or_default().trim_end();
+        let indicators = header.strip_prefix(['|', '>'])?;
+        if !indicators
+            .chars()
+            .all(|c| c == '+' || c == '-' || c.is_ascii_digit())
+        {
+            return None;
+        }
+        Some(if self.key.is_some() {
+            self.column
+        } else {
+            self.indent
+        })
+    }
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Return true if the cursor is in a block scalar. The blank lines belong to
+/// the block if the cursor or the next content is indented into it.
+fn in_block(lines: &[&str], row: usize, col: usize) -> bool {
+    let row = row.min(lines.len());
+    let start = lines[..row]
+        .iter()
+        .rposition(|l| l.starts_with("---") || l.starts_with("..."))
+        .map_or(0, |i| i + 1);
+    let mut parent = None;
+    for line in &lines[start..row] {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if parent.is_some_and(|p| indent(line) > p) {
+            continue;
+        }
+        parent = Line::new(line).block();
+    }
+    let Some(parent) = parent else {
+        return false;
+    };
+    let line = lines.get(row).copied().unwrap_or_default();
+    if !line.trim().is_empty() {
+        return indent(line) > parent;
+    }
+    let rest = lines.get(row + 1..).unwrap_or_default();
+    let next = rest.iter().find(|l| !l.trim().is_empty());
+    col > parent || next.is_some_and(|l| indent(l) > parent)
+}
+
+/// The cursor context of the YAML source, found by the indentation.
+pub(super) struct Cursor<'a> {
+    /// The keys from the document root to the parent map.
+    pub(super) path: Vec<&'a str>,
+    /// The key of the value, or `None` in the key position.
+    pub(super) key: Option<&'a str>,
+    /// The span of the key at the cursor, in bytes.
+    pub(super) span: (usize, usize),
+}
+
+impl<'a> Cursor<'a> {
+    /// Get the context before the cursor, return `None` if the cursor is in a
+    /// block scalar.
+    pub(super) fn new(lines: &[&'a str], row: usize, col: usize) -> Option<Self> {
+        if in_block(lines, row, col) {
+            return None;
+        }
+        let line = lines.get(row).copied().unwrap_or_default();
+        let head = Line::new(&line[..col.min(line.len())]);
+        let full = Line::new(line);
+        let path = parents(lines, row, head.column)?;
+        let key = head.key;
+        let span = match full.key {
+            Some(key) => (full.column, full.column + key.len()),
+            None => (full.column, full.column),
+        };
+        Some(Self { path, key, span })
+    }
+}
+
+/// The keys of the parent maps, from the root to the nearest one.
+fn parents<'a>(lines: &[&'a str], row: usize, mut column: usize) -> Option<Vec<&'a str>> {
+    let mut path = Vec::new();
+    for line in lines[..row].iter().rev() {
+        if line.starts_with("---") || line.starts_with("...") {
+            break;
+        }
+        let line = Line::new(line);
+        if line.is_blank() || line.column >= column {
+            continue;
+        }
+        // A multi-line scalar, or a sequence without a key
+        let key = line.key?;
+        if !(line.value.is_empty() || line.value.starts_with(['&', '!', '#'])) {
+            return None;
+        }
+        path.push(key);
+        column = line.column;
+        if column == 0 && line.indent == 0 {
+            break;
+        }
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// The slide link at the cursor, a `#/id` link or a `[[id]]` link.
+pub(super) fn link_at(line: &str, col: usize) -> Option<&str> {
+    for (i, _) in line.match_indices("#/") {
+        let anchor = &line[i + 2..];
+        let end = anchor
+            .find([')', '"', '\'', ' ', ']', ','])
+            .unwrap_or(anchor.len());
+        if (i..=i + 2 + end).contains(&col) {
+            return Some(&anchor[..end]).filter(|s| !s.is_empty());
+        }
+    }
+    let mut start = 0;
+    while let Some(i) = line[start..].find("[[") {
+        let i = start + i;
+        let j = i + line[i..].find("]]")?;
+        let Some((id, _)) = slide_link(&line[i + 2..j]) else {
+            start = i + 2;
+            continue;
+        };
+        if (i..=j + 2).contains(&col) {
+            return Some(id);
+        }
+        start = j + 2;
+    }
+    None
+}
+
+/// Return true if the cursor is typing a slide link.
+pub(super) fn in_link(line: &str, col: usize) -> bool {
+    let head = &line[..col.min(line.len())];
+    [("#/", &[')', '"', '\'', ' '][..]), ("[[", &[']', '|'][..])]
+        .into_iter()
+        .any(|(start, ends)| head.rfind(start).is_some_and(|i| !head[i..].contains(ends)))
+}
+
+/// Convert the UTF-16 column of LSP into the byte index.
+pub(super) fn byte_index(line: &str, utf16: u32) -> usize {
+    let mut n = 0;
+    for (i, c) in line.char_indices() {
+        if n >= utf16 as usize {
+            return i;
+        }
+        n += c.len_utf16();
+    }
+    line.len()
+}
+
+/// Convert the byte index into the UTF-16 column of LSP.
+pub(super) fn utf16(line: &str, byte: usize) -> u32 {
+    line[..byte.min(line.len())].encode_utf16().count() as u32
+}
@@ -0,0 +1,143 @@
+use crate::project::root_schema;
+use serde_json::Value;
+
+/// The fields of the project documents, from the JSON schema.
+pub(super) struct Fields(Value);
+
+impl Fields {
+    pub(super) fn new() -> Self {
+        Self(root_schema().to_value())
+    }
+
+    /// Resolve the references, the variants and the sequence items into the
+    /// leaf schemas.
+    fn leaves<'a>(&'a self, schema: &'a Value, out: &mut Vec<&'a Value>) {
+        if let Some(r) = schema.get("$ref").and_then(Value::as_str) {
+            if let Some(s) = r.strip_prefix('#').and_then(|p| self.0.pointer(p)) {
+                self.leaves(s, out);
+            }
+            return;
+        }
+        let mut composite = false;
+        for key in ["anyOf", "oneOf", "allOf"] {
+            for s in schema
+                .get(key)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                composite = true;
+                self.leaves(s, out);
+            }
+        }
+        if let Some(items) = schema.get("items") {
+            self.leaves(items, out);
+        }
+        if !composite {
+            out.push(schema);
+        }
+    }
+
+    /// The property schemas of the key in the leaf schemas.
+    fn props<'a>(&'a self, schemas: &[&'a Value], key: &str) -> Vec<&'a Value> {
+        schemas
+            .iter()
+            .filter_map(|s| {
+                s.get("properties")
+                    .and_then(|props| props.get(key))
+                    .or_else(|| s.get("additionalProperties").filter(|s| s.is_object()))
+            })
+            .collect()
+    }
+
+    /// The leaf schemas of the maps at the path.
+    fn at(&self, path: &[&str]) -> Vec<&Value> {
+        let mut schemas = Vec::new();
+        self.leaves(&self.0, &mut schemas);
+        for key in path {
+            let props = self.props(&schemas, key);
+            schemas.clear();
+            for s in props {
+                self.leaves(s, &mut schemas);
+            }
+        }
+        schemas
+    }
+
+    /// The keys and their descriptions of the map at the path.
+    pub(super) fn keys(&self, path: &[&str]) -> Vec<(&str, String)> {
+        let mut keys = Vec::<(&str, String)>::new();
+        for s in self.at(path) {
+            for (key, prop) in s
+                .get("properties")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                if !keys.iter().any(|(k, _)| k == key) {
+                    keys.push((key, self.describe(prop)));
+                }
+            }
+        }
+        keys
+    }
+
+    /// The values and their descriptions of the key in the map at the path.
+    pub(super) fn values(&self, path: &[&str], key: &str) -> Vec<(String, String)> {
+        let mut values = Vec::new();
+        let mut schemas = Vec::new();
+        for s in self.props(&self.at(path), key) {
+            self.leaves(s, &mut schemas);
+        }
+        for s in schemas {
+            let doc = s
+                .get("description")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let consts = s
+                .get("enum")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            for v in consts.iter().chain(s.get("const")) {
+                if let Some(v) = v.as_str() {
+                    values.push((v.to_string(), doc.to_string()));
+                }
+            }
+            if s.get("type").and_then(Value::as_str) == Some("boolean") {
+                values.push(("true".to_string(), String::new()));
+                values.push(("false".to_string(), String::new()));
+            }
+        }
+        values.dedup_by(|a, b| a.0 == b.0);
+        values
+    }
+
+    /// The document of the key in the map at the path.
+    pub(super) fn doc(&self, path: &[&str], key: &str) -> Option<String> {
+        let props = self.props(&self.at(path), key);
+        let doc = props.first().map(|s| self.describe(s))?;
+        Some(format!("**{key}**\n\n{doc}"))
+    }
+
+    /// The description of a property, from itself or its type.
+    fn describe<'a>(&'a self, mut schema: &'a Value) -> String {
+        loop {
+            if let Some(doc) = schema.get("description").and_then(Value::as_str) {
+                return doc.to_string();
+            }
+            let next = match schema.get("allOf").and_then(Value::as_array) {
+                Some(all) if all.len() == 1 => &all[0],
+                _ => schema,
+            };
+            match next
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|r| self.0.pointer(r.strip_prefix('#')?))
+            {
+                Some(s) => schema = s,
+                None => return String::new(),
+            }
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use crate::{
     project::load,
-    update::{archive, update},
+    update::{archive, mathjax, update_mathjax, update_reveal},
 };
 use binstall_zip::ZipArchive;
 use std::{
     fs,
-    io::Result,
+    io::{Error, ErrorKind, Result},
     path::{Path, PathBuf},
 };
 
@@ -33,18 +33,31 @@ where
     Ok(())
 }
 
-pub(crate) fn extract<D>(d: D) -> Result<()>
+/// Extract Reveal.js archive, and the MathJax archive into it in the offline
+/// mode.
+pub(crate) fn extract<D>(d: D, offline: bool) -> Result<()>
 where
     D: AsRef<Path>,
 {
+    let d = d.as_ref();
     let path = std::env::current_exe()?.with_file_name(concat!(archive!(), ".zip"));
+    let mathjax = path.with_file_name(concat!(mathjax!(), ".zip"));
     if !path.exists() {
-        update()?;
+        update_reveal()?;
+    }
+    if offline && !mathjax.exists() {
+        update_mathjax()?;
     }
     ZipArchive::new(fs::File::open(path)?)
         .unwrap()
-        .extract(d.as_ref())
+        .extract(d)
         .unwrap();
+    if offline {
+        ZipArchive::new(fs::File::open(mathjax)?)
+            .unwrap()
+            .extract(d.join(archive!()))
+            .unwrap();
+    }
     Ok(())
 }
 
@@ -56,7 +69,10 @@ where
 }
 
 /// Pack project to an archive.
-pub fn pack<P, D>(path: P, dist: D, project: &str) -> Result<()>
+///
+/// The offline mode includes MathJax, so the package can work without network.
+/// The strict mode reports the unknown keys as warnings.
+pub fn pack<P, D>(path: P, dist: D, project: &str, offline: bool, strict: bool) -> Result<()>
 where
     P: AsRef<Path>,
     D: AsRef<Path>,
@@ -67,19 +83,22 @@ where
         println!("Remove {dist:?}");
         fs::remove_dir_all(dist)?;
     }
-    extract(".")?;
-    pack_inner(project).map_err(|e| {
+    extract(".", offline)?;
+    if let Err(e) = pack_inner(project, offline, strict) {
         fs::remove_dir_all(archive!()).unwrap_or_default();
-        e
-    })?;
+        return Err(e);
+    }
     fs::rename(archive!(), dist)?;
     println!("Done");
     Ok(())
 }
 
-fn pack_inner(project: &str) -> Result<()> {
+fn pack_inner(project: &str, offline: bool, strict: bool) -> Result<()> {
     let archive = Path::new(archive!());
-    let contents = load(&fs::read_to_string(project)?, "", false)?;
+    let contents = load(project, "", false, offline, strict, &mut Vec::new()).map_err(|d| {
+        let msg = format!("could not load the project due to {} error(s)", d.errors());
+        Error::new(ErrorKind::InvalidData, msg)
+    })?;
     fs::write(archive.join("index.html"), contents)?;
     for assets in listdir(".")? {
         let name = assets.file_name().unwrap().to_str().unwrap();
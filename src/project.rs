@@ -75,6 +75,31 @@
 //! Please be aware that anchors cannot be referenced between different
 //! documents.
 //!
+//! ### Include Files
+//!
+//! A large project can be split into several YAML files with the `!!include`
+//! tag, the path is relative to the including file.
+//!
+//! ```yaml
+//! ## reveal.yaml
+//! - title: Introduction
+//! - !!include chapters/chapter1.yaml
+//! - title: Appendix
+//!   sub: !!include chapters/appendix.yaml
+//! ```
+//!
+//! If an included file is placed in a sequence, its documents and sequences
+//! are flatten into the sequence, so the included file can be written in
+//! either list form or multi-document form. Otherwise, the included file will
+//! replace the tagged node.
+//!
+//! ```yaml
+//! ## chapters/chapter1.yaml
+//! title: Chapter 1
+//! ---
+//! title: Chapter 2
+//! ```
+//!
 //! ### Layout System
 //!
 //! There are two layout types, called "horizontal stack" ([`Content::h_stack`])
@@ -101,6 +126,18 @@
 //!   - doc: Right
 //! ```
 //!
+//! The widths of the horizontal stacks are equal by default, use
+//! [`Content::ratio`] of the blocks to change them.
+//!
+//! ```yaml
+//! h-stack:
+//!   - doc: Left
+//!     ratio: 2
+//!   - doc: Right
+//! ```
+//!
+//! For the complex layouts, please see the [`Grid`] layout.
+//!
 //! ### Sized Attribute
 //!
 //! The images and resizeable items are support [`Sized`] attribute, which
@@ -127,6 +164,27 @@
 //!   autoplay: true
 //! ```
 //!
+//! ### Cross-references
+//!
+//! The captioned images and tables, and the numbered equations are numbered
+//! across the deck. Give them the labels, then refer them in Markdown, the
+//! references become the links to the slides.
+//!
+//! ```yaml
+//! - doc: As shown in @fig:arch, @tab:bench and @eq:euler.
+//! - img:
+//!     src: img/arch.png
+//!     label: Architecture
+//!     id: fig:arch
+//!   table:
+//!     src: data/bench.csv
+//!     caption: Benchmark
+//!     id: tab:bench
+//!   math: e^{i\pi} + 1 = 0 \label{eq:euler}
+//! ```
+//!
+//! The undefined references are errors.
+//!
 //! ### Reveal Plugins
 //!
 //! The plugins excluding `markdown` are enabled by default.
@@ -138,7 +196,10 @@
 //! Generally, `reveal.yaml` divides into two parts, the first part will be
 //! deserialized to [`Metadata`], and the second part will be deserialized to
 //! [`Slides`], other parts will be ignored. YAML parser will not check extra
-//! key values.
+//! key values, unless the strict mode is enabled by [`Metadata::strict`] or the
+//! `--strict` flag, then the unknown keys are reported as warnings with the
+//! suggestions, such as `titel` to `title` and `auto_animate` to
+//! `auto-animate`.
 //!
 //! During the deserialization, "flatten" field means the child field will be
 //! inherited by the parent, such as [`Sized`].
@@ -146,66 +207,230 @@
 //! Most of functions are planed to be demonstrated in the help page.
 //! Open the help page by adding `/help/` after URL, like `http://localhost:8080/help/`.
 pub use self::{
-    background::*, content::*, footer::*, js_option::*, js_plugin::*, metadata::*, slides::*,
-    to_html::*, wrap_string::*,
+    background::*, citation::*, content::*, diagnostic::*, footer::*, js_option::*, js_plugin::*,
+    math::*, metadata::*, outline::*, schema::json_schema, slides::*, title::*, to_html::*,
+    wrap_string::*,
+};
+pub(crate) use self::{
+    cross_ref::slide_link,
+    schema::root as root_schema,
+    symbols::{Symbol, Symbols},
+    validate::{archive_files, validate},
+};
+use self::{
+    cross_ref::{mark_refs, resolve_refs},
+    diagnostic::check,
+    expand::{Expander, Vars},
+    lint::Linter,
+    source::Sources,
 };
 use serde::Deserialize as _;
-use std::io::{Error as IoError, ErrorKind};
-use yaml_peg::{indicated_msg, parse, serde::SerdeError, NodeRc};
+use std::{
+    fs::read_to_string,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+};
+use yaml_peg::{serde::SerdeError, NodeRc};
 
 mod background;
+mod citation;
 mod content;
+mod cross_ref;
+mod diagnostic;
+mod expand;
 mod footer;
 mod js_option;
 mod js_plugin;
+mod lint;
+mod math;
 mod metadata;
+mod outline;
+mod schema;
 mod slides;
+mod source;
+mod symbols;
+mod title;
 mod to_html;
+mod validate;
 mod wrap_string;
 
-pub(crate) fn load(doc: &str, mount: &str, auto_reload: bool) -> Result<String, IoError> {
-    let yaml = parse(doc).map_err(|e| IoError::new(ErrorKind::InvalidData, e.to_string()))?;
-    let disp = |SerdeError { msg, pos }| {
-        let ind_msg = indicated_msg(doc.as_bytes(), pos);
-        eprintln!("{msg}\n{ind_msg}");
-        IoError::new(ErrorKind::InvalidData, msg)
-    };
-    let to_slides_flatten = |ns: &[NodeRc]| {
-        ns.iter()
-            .filter(|n| !n.is_null())
-            .cloned()
-            .map(Option::<ChapterSlide>::deserialize)
-            .filter_map(|r| match r {
-                Ok(s) => s.map(Ok),
-                Err(e) => Some(Err(e)),
-            })
-            .collect::<Result<Vec<_>, _>>()
-    };
-    let (metadata, slides) = match yaml.as_slice() {
-        [] => (Metadata::default(), Slides::single("Hello", "World!")),
-        [n1] => {
-            let slides = Vec::deserialize(n1.clone()).map_err(disp)?;
-            (Metadata::default(), Slides { slides })
+/// Load the project, the loaded files are collected even if there are errors.
+pub(crate) fn load<P>(
+    path: P,
+    mount: &str,
+    auto_reload: bool,
+    offline: bool,
+    strict: bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<String, Diagnostics>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    files.push(path.to_path_buf());
+    let doc = read_to_string(path).map_err(|e| Diagnostic::new(format!("{}: {e}", path.display())));
+    load_doc(&doc?, path, mount, auto_reload, offline, strict, files)
+}
+
+pub(crate) fn load_doc(
+    doc: &str,
+    path: &Path,
+    mount: &str,
+    auto_reload: bool,
+    offline: bool,
+    strict: bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<String, Diagnostics> {
+    let (doc, warnings) = load_inner(doc, path, mount, auto_reload, offline, strict, files)
+        .inspect_err(Diagnostics::print)?;
+    warnings.print();
+    Ok(doc)
+}
+
+/// A parsed project before rendering.
+struct Project {
+    sources: Sources,
+    /// The metadata node.
+    meta: Option<NodeRc>,
+    metadata: Metadata,
+    slides: Vec<ChapterSlide>,
+    /// The nodes of the chapter slides.
+    nodes: Vec<NodeRc>,
+    warnings: Vec<Diagnostic>,
+}
+
+impl Project {
+    /// Parse and deserialize the project, and collect the loaded files.
+    fn parse(
+        doc: &str,
+        path: &Path,
+        strict: bool,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<Self, Diagnostics> {
+        let mut sources = Sources::default();
+        let yaml = sources.parse(path, doc);
+        files.extend(sources.files().map(Path::to_path_buf));
+        let yaml = match yaml {
+            Ok(yaml) => yaml,
+            Err(Diagnostics(diags)) => return Err(sources.diagnostics(diags)),
+        };
+        let (n1, ns) = match yaml.as_slice() {
+            [] => {
+                return Ok(Self {
+                    sources,
+                    meta: None,
+                    metadata: Metadata::default(),
+                    slides: Slides::single("Hello", "World!").slides,
+                    nodes: Vec::new(),
+                    warnings: Vec::new(),
+                })
+            }
+            // The leading document is metadata, unless it is a valid slide
+            [n1, ns @ ..]
+                if !ns.is_empty()
+                    && (Metadata::deserialize(n1.clone()).is_ok()
+                        || ChapterSlide::deserialize(n1.clone()).is_err()) =>
+            {
+                (Some(n1), ns)
+            }
+            ns => (None, ns),
+        };
+        let (vars, expander) = match (Vars::new(n1), Expander::new(n1)) {
+            (Ok(vars), Ok(expander)) => (vars, expander),
+            (Err(e), _) | (_, Err(e)) => return Err(sources.diagnostics(e.0)),
+        };
+        let mut diags = Vec::new();
+        let meta = match n1.map(|n| vars.metadata(n, &sources)).transpose() {
+            Ok(n1) => n1,
+            Err(e) => {
+                diags.extend(e.0);
+                None
+            }
+        };
+        let metadata = meta
+            .as_ref()
+            .and_then(|n| check::<Metadata>(n, &mut diags))
+            .unwrap_or_default();
+        // The unknown keys are collected before the errors
+        let mut warnings = Vec::new();
+        let strict = strict || metadata.strict;
+        if strict {
+            if let Some(n) = &meta {
+                Linter::new::<Metadata>().lint(n, &mut warnings);
+            }
         }
-        ns @ [n1, ns_sub @ ..] => {
-            if let Ok(metadata) = Metadata::deserialize(n1.clone()) {
-                let slides = to_slides_flatten(ns_sub).map_err(disp)?;
-                (metadata, Slides { slides })
-            } else {
-                let slides = to_slides_flatten(ns).map_err(disp)?;
-                (Metadata::default(), Slides { slides })
+        let linter = Linter::new::<ChapterSlide>();
+        let mut slides = Vec::new();
+        let mut nodes = Vec::new();
+        for n in ns.iter().filter(|n| !n.is_null()) {
+            let n = match expander
+                .expand(n, &sources)
+                .and_then(|n| vars.interpolate(&n, &sources))
+            {
+                Ok(n) => n,
+                Err(e) => {
+                    diags.extend(e.0);
+                    continue;
+                }
+            };
+            for n in n.as_seq().unwrap_or_else(|_| vec![n.clone()]) {
+                if n.is_null() {
+                    continue;
+                }
+                if strict {
+                    linter.lint(&n, &mut warnings);
+                }
+                if let Some(mut chapter) = check::<ChapterSlide>(&n, &mut diags) {
+                    chapter.set_pos(&n);
+                    slides.push(chapter);
+                    nodes.push(n);
+                }
             }
         }
-    };
-    Ok(metadata.build(slides, mount, auto_reload))
+        if !diags.is_empty() {
+            warnings.extend(diags);
+            return Err(sources.diagnostics(warnings));
+        }
+        Ok(Self { sources, meta, metadata, slides, nodes, warnings })
+    }
+}
+
+fn load_inner(
+    doc: &str,
+    path: &Path,
+    mount: &str,
+    auto_reload: bool,
+    offline: bool,
+    strict: bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<(String, Diagnostics), Diagnostics> {
+    let Project { sources, metadata, slides, mut warnings, .. } =
+        Project::parse(doc, path, strict, files)?;
+    let base = path.parent().unwrap_or(Path::new(""));
+    match metadata.render(Slides { slides }, base, mount, auto_reload, offline, files) {
+        Ok(doc) => Ok((doc, sources.diagnostics(warnings))),
+        Err(diags) => {
+            warnings.extend(diags);
+            Err(sources.diagnostics(warnings))
+        }
+    }
+}
+
+/// Show the error overlay on the page.
+pub(crate) fn with_overlay(page: &str, diags: &Diagnostics) -> String {
+    match page.rfind("</body>") {
+        Some(i) => format!("{}{}{}", &page[..i], diags.to_html(), &page[i..]),
+        None => page.to_string() + &diags.to_html(),
+    }
 }
 
-pub(crate) fn error_page(e: IoError) -> String {
-    let slides = Slides::single("Error", format!("```\n{e}\n```"));
-    Metadata::default().build(slides, "/static/", true)
+pub(crate) fn error_page(diags: Diagnostics) -> String {
+    with_overlay(&single_page("Error", ""), &diags)
 }
 
 pub(crate) fn single_page(title: &str, doc: &str) -> String {
     let slides = Slides::single(title, doc);
-    Metadata::default().build(slides, "/static/", true)
+    Metadata::default()
+        .build(slides, "/static/", true)
+        .expect("built-in template")
 }
@@ -2,7 +2,7 @@ use super::*;
 use yaml_peg::serde::Stringify;
 
 /// Background setting.
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, schemars::JsonSchema)]
 #[serde(untagged)]
 pub enum Background {
     /// [Color Backgrounds](https://revealjs.com/backgrounds/#color-backgrounds),
@@ -28,18 +28,20 @@ impl ToHtml for Background {
 }
 
 /// Image backgrounds setting.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct ImgBackground {
     /// Background source.
     pub src: String,
     /// Background size.
+    #[schemars(with = "schema::Stringify")]
     pub size: Stringify,
     /// Background position.
     pub position: String,
     /// Background repeat. (repeat / no-repeat)
     pub repeat: String,
     /// Background opacity from zero to one.
+    #[schemars(with = "schema::Stringify")]
     pub opacity: Stringify,
 }
 
@@ -0,0 +1,280 @@
+use super::*;
+use biblatex::{Bibliography, ChunksExt as _, Entry, Person};
+use std::{cell::RefCell, fmt::Write};
+
+/// Citation styles.
+#[derive(Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CiteStyle {
+    /// Numbered by the citing order, such as `[1]`, the default style.
+    #[default]
+    Numeric,
+    /// Author and year, such as `(Knuth, 1984)`.
+    AuthorYear,
+    /// Author abbreviation and year, such as `[Knu84]`.
+    Alphabetic,
+}
+
+/// Citation option, works with [`Metadata::bibliography`].
+///
+/// ```yaml
+/// bibliography: refs.bib
+/// citation:
+///   style: author-year
+///   footnote: true
+/// ```
+///
+/// Cite the entries in Markdown by `[@key]`, multiple keys are separated by
+/// semicolons, and the locator can be given after a comma, such as
+/// `[@knuth1984, p. 3; @lamport1994]`. The unknown keys are errors.
+///
+/// The cited entries are listed on the references slide at the end, which has
+/// an id "references".
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct CitationOption {
+    /// Citation style, `numeric`, `author-year` or `alphabetic`.
+    pub style: CiteStyle,
+    /// List the cited entries at the bottom of each slide.
+    pub footnote: bool,
+    /// Title of the references slide, "References" by default.
+    pub title: String,
+}
+
+impl Default for CitationOption {
+    fn default() -> Self {
+        Self {
+            style: CiteStyle::default(),
+            footnote: false,
+            title: "References".to_string(),
+        }
+    }
+}
+
+/// The loaded bibliography and the cited keys.
+pub struct Bib {
+    bib: Bibliography,
+    option: CitationOption,
+    cited: RefCell<Vec<String>>,
+    slide: RefCell<Vec<String>>,
+}
+
+fn field(entry: &Entry, key: &str) -> String {
+    entry
+        .get(key)
+        .map(|chunks| chunks.format_verbatim())
+        .unwrap_or_default()
+}
+
+fn authors(entry: &Entry) -> Vec<Person> {
+    entry
+        .author()
+        .ok()
+        .or_else(|| entry.editors().ok()?.into_iter().next().map(|(p, _)| p))
+        .unwrap_or_default()
+}
+
+fn year(entry: &Entry) -> String {
+    let year = field(entry, "year");
+    if year.is_empty() {
+        field(entry, "date").chars().take(4).collect()
+    } else {
+        year
+    }
+}
+
+impl Bib {
+    /// Parse the BibTeX / BibLaTeX source.
+    pub fn new(src: &str, option: CitationOption) -> Result<Self, String> {
+        let bib = Bibliography::parse(src).map_err(|e| e.to_string())?;
+        Ok(Self {
+            bib,
+            option,
+            cited: Default::default(),
+            slide: Default::default(),
+        })
+    }
+
+    fn label(&self, key: &str) -> String {
+        let Some(entry) = self.bib.get(key) else {
+            return "?".to_string();
+        };
+        let authors = authors(entry);
+        let year = year(entry);
+        match self.option.style {
+            CiteStyle::Numeric => {
+                let cited = self.cited.borrow();
+                let n = cited.iter().position(|k| k == key).unwrap_or(cited.len());
+                (n + 1).to_string()
+            }
+            CiteStyle::AuthorYear => {
+                let name = match authors.as_slice() {
+                    [] => key.to_string(),
+                    [a] => a.name.clone(),
+                    [a, b] => format!("{} and {}", a.name, b.name),
+                    [a, ..] => format!("{} et al.", a.name),
+                };
+                format!("{name}, {year}")
+            }
+            CiteStyle::Alphabetic => {
+                let name = match authors.as_slice() {
+                    [] => key.chars().take(3).collect(),
+                    [a] => a.name.chars().take(3).collect(),
+                    ps => {
+                        let mut s = ps
+                            .iter()
+                            .take(3)
+                            .filter_map(|p| p.name.chars().next())
+                            .collect::<String>();
+                        if ps.len() > 3 {
+                            s.push('+');
+                        }
+                        s
+                    }
+                };
+                let year = year.get(year.len().saturating_sub(2)..).unwrap_or_default();
+                format!("{name}{year}")
+            }
+        }
+    }
+
+    /// The citation of the keys with the optional locators.
+    fn cite(&self, items: &[(&str, &str)], ctx: &Ctx) -> String {
+        let mut labels = Vec::new();
+        for &(key, locator) in items {
+            if self.bib.get(key).is_none() {
+                ctx.error(format!("unknown citation key `{key}`"));
+            } else {
+                for cited in [&self.cited, &self.slide] {
+                    let mut cited = cited.borrow_mut();
+                    if !cited.iter().any(|k| k == key) {
+                        cited.push(key.to_string());
+                    }
+                }
+            }
+            let label = html_escape(&self.label(key)) + &html_escape(locator).wrap(", ", "");
+            labels.push(format!("<a href=\"#/references\">{label}</a>"));
+        }
+        let (open, sep, close) = match self.option.style {
+            CiteStyle::AuthorYear => ("(", "; ", ")"),
+            _ => ("[", ", ", "]"),
+        };
+        format!(
+            "<span class=\"cite\">{open}{}{close}</span>",
+            labels.join(sep)
+        )
+    }
+
+    /// Replace the `[@key]` citations in the plain text, return HTML.
+    pub(crate) fn replace(&self, text: &str, ctx: &Ctx) -> String {
+        let mut s = String::new();
+        let mut rest = text;
+        while let Some(i) = rest.find("[@") {
+            let Some(len) = rest[i..].find(']') else {
+                break;
+            };
+            let inner = &rest[i + 1..i + len];
+            let items = inner
+                .split(';')
+                .map(|item| {
+                    let (key, locator) = item.split_once(',').unwrap_or((item, ""));
+                    let key = key.trim().strip_prefix('@')?;
+                    let valid = !key.is_empty()
+                        && key
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || "_-:./".contains(c));
+                    valid.then_some((key, locator.trim()))
+                })
+                .collect::<Option<Vec<_>>>();
+            s += &html_escape(&rest[..i]);
+            match items {
+                Some(items) => s += &self.cite(&items, ctx),
+                None => s += &html_escape(&rest[i..i + len + 1]),
+            }
+            rest = &rest[i + len + 1..];
+        }
+        s + &html_escape(rest)
+    }
+
+    fn entry(&self, key: &str) -> String {
+        let Some(entry) = self.bib.get(key) else {
+            return String::new();
+        };
+        let authors = authors(entry)
+            .iter()
+            .map(|p| {
+                let name = format!("{} {} {}", p.given_name, p.prefix, p.name);
+                name.split_whitespace().collect::<Vec<_>>().join(" ")
+            })
+            .collect::<Vec<_>>();
+        let authors = match authors.as_slice() {
+            [] => String::new(),
+            [a] => a.clone(),
+            [init @ .., last] => format!("{}, and {last}", init.join(", ")),
+        };
+        let venue = [
+            "journal",
+            "journaltitle",
+            "booktitle",
+            "publisher",
+            "school",
+        ]
+        .into_iter()
+        .map(|k| field(entry, k))
+        .find(|v| !v.is_empty())
+        .unwrap_or_default();
+        let mut s = html_escape(&authors).wrap("", ". ");
+        write!(s, "<em>{}</em>.", html_escape(&field(entry, "title"))).unwrap();
+        let tail = [venue, year(entry)]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        s + &html_escape(&tail).wrap(" ", ".")
+    }
+
+    fn list(&self, keys: &[String]) -> String {
+        let mut items = keys
+            .iter()
+            .map(|key| (self.label(key), self.entry(key)))
+            .collect::<Vec<_>>();
+        if self.option.style != CiteStyle::Numeric {
+            items.sort();
+        }
+        let items = items
+            .into_iter()
+            .map(|(label, entry)| match self.option.style {
+                CiteStyle::AuthorYear => format!("<li>{entry}</li>\n"),
+                _ => format!(
+                    "<li><span class=\"cite-label\">[{}]</span> {entry}</li>\n",
+                    html_escape(&label)
+                ),
+            })
+            .collect::<String>();
+        items.wrap("<ul class=\"references\">\n", "</ul>\n")
+    }
+
+    /// The cited entries of the current slide if the footnote is enabled.
+    pub(crate) fn footnote(&self) -> String {
+        let keys = self.slide.take();
+        if !self.option.footnote {
+            return String::new();
+        }
+        self.list(&keys)
+            .wrap("<div class=\"cite-footnote\">\n", "</div>\n")
+    }
+
+    /// The references slide.
+    pub(crate) fn references(&self, ctx: &Ctx) -> String {
+        let keys = self.cited.borrow();
+        if keys.is_empty() {
+            return String::new();
+        }
+        let title = md2html(&self.option.title.wrap("# ", ""), ctx);
+        format!(
+            "<section{} id=\"references\">\n{title}{}</section>",
+            ctx.background,
+            self.list(&keys)
+        )
+    }
+}
@@ -1,17 +1,26 @@
-pub use self::{frag_map::*, lay_img::*, marked::*, media::*, sized::*};
+pub use self::{
+    chart::*, code::*, dot::*, frag_map::*, grid::*, highlight::*, lay_img::*, marked::*, media::*,
+    sized::*, table::*,
+};
 use super::*;
 use yaml_peg::serde::InlineList;
 
+mod chart;
+mod code;
+mod dot;
 mod frag_map;
+mod grid;
+mod highlight;
 mod lay_img;
 mod marked;
 mod media;
 mod sized;
+mod table;
 
 /// A content block, which visualize all contents in the layout.
 ///
 /// The attributes will placed in the following order.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct Content {
     /// [Fit texts](https://revealjs.com/layout/#fit-text).
@@ -21,10 +30,19 @@ pub struct Content {
     pub fit: Vec<String>,
     /// Multiline Markdown text, accept HTML.
     ///
-    /// The star * symbol of inline math needs to escaped.
+    /// The star * symbol of inline math needs to escaped, except for the
+    /// build-time renderer or the math passthrough. (see [`MathOption`] and
+    /// [`MarkdownOption::math`])
     /// ```markdown
     /// $x_a^\*$ and $x_b^\*$
     /// ```
+    ///
+    /// The info string of the fenced code blocks accepts the step highlights
+    /// and the attributes.
+    /// ````markdown
+    /// ```rust [1-3|5] {start=10 trim noescape .my-class #my-id file=src/main.rs}
+    /// ```
+    /// ````
     pub doc: String,
     /// Include a Markdown file from path, append after `doc`.
     pub include: String,
@@ -33,6 +51,8 @@ pub struct Content {
     #[serde(rename = "include-html")]
     pub include_html: bool,
     /// Multiline LaTex math without `$$` / `\[\]` brackets.
+    ///
+    /// The renderer is decided by [`Metadata::math`].
     /// ```yaml
     /// math: |
     ///   \begin{cases}
@@ -42,6 +62,44 @@ pub struct Content {
     ///   \end{cases}
     /// ```
     pub math: String,
+    /// Include source code from files.
+    ///
+    /// ```yaml
+    /// code:
+    ///   src: src/main.rs
+    ///   lines: 10-40
+    ///   highlight: 1-3|5
+    /// ```
+    #[schemars(with = "schema::InlineList<Code>")]
+    pub code: InlineList<Code>,
+    /// Render CSV / TSV files as tables.
+    ///
+    /// ```yaml
+    /// table:
+    ///   src: data/bench.csv
+    ///   highlight: max
+    /// ```
+    #[schemars(with = "schema::InlineList<Table>")]
+    pub table: InlineList<Table>,
+    /// Build-time SVG charts.
+    ///
+    /// ```yaml
+    /// chart:
+    ///   labels: [A, B, C]
+    ///   series:
+    ///     - name: Time
+    ///       data: [3, 5, 2]
+    /// ```
+    #[schemars(with = "schema::InlineList<Chart>")]
+    pub chart: InlineList<Chart>,
+    /// Graphviz DOT graphs, laid out at build time.
+    ///
+    /// ```yaml
+    /// dot:
+    ///   graph: digraph { a -> b; }
+    /// ```
+    #[schemars(with = "schema::InlineList<Dot>")]
+    pub dot: InlineList<Dot>,
     /// Embed images.
     ///
     /// ```yaml
@@ -53,14 +111,32 @@ pub struct Content {
     /// - src: img/image1.png
     /// - src: img/image2.png
     /// ```
+    #[schemars(with = "schema::InlineList<Img>")]
     pub img: InlineList<Img>,
     /// Embed videos. Same as `img`.
+    #[schemars(with = "schema::InlineList<Video>")]
     pub video: InlineList<Video>,
     /// Embed `<iframe>` structures, such as YouTube videos.
+    #[schemars(with = "schema::InlineList<IFrame>")]
     pub iframe: InlineList<IFrame>,
     /// Layout stack for images.
     #[serde(rename = "lay-img")]
+    #[schemars(with = "schema::InlineList<LayImg>")]
     pub lay_img: InlineList<LayImg>,
+    /// Ordered content blocks, placed after the above blocks.
+    ///
+    /// Each item is a content block, so the blocks can be repeated and placed
+    /// in any order.
+    ///
+    /// ```yaml
+    /// blocks:
+    ///   - doc: First paragraph.
+    ///   - img:
+    ///       src: img/image.png
+    ///   - doc: Second paragraph.
+    ///   - math: E = mc^2
+    /// ```
+    pub blocks: Vec<Self>,
     /// Fragment option.
     #[serde(flatten)]
     pub frag: FragMap,
@@ -76,6 +152,37 @@ pub struct Content {
     /// Vertical stack with border.
     #[serde(rename = "v-stack-border")]
     pub v_stack_border: Vec<Self>,
+    /// Grid layout.
+    pub grid: Grid,
+    /// Width ratio of this block in the horizontal stacks, `1` by default.
+    ///
+    /// ```yaml
+    /// h-stack:
+    ///   - doc: Wider
+    ///     ratio: 2
+    ///   - doc: Narrower
+    /// ```
+    pub ratio: Option<f32>,
+}
+
+/// Percentage widths from the ratios of the blocks, equal widths if the
+/// ratios are all zero.
+fn widths(blocks: &[Content], ctx: &Ctx) -> Vec<f32> {
+    let n = blocks.len();
+    let equal = vec![100. / n as f32; n];
+    let ratio = blocks
+        .iter()
+        .map(|c| c.ratio.unwrap_or(1.))
+        .collect::<Vec<_>>();
+    if ratio.iter().any(|r| !r.is_finite() || *r < 0.) {
+        ctx.error("stack ratio must be a non-negative number");
+        return equal;
+    }
+    let sum = ratio.iter().sum::<f32>();
+    if sum <= 0. {
+        return equal;
+    }
+    ratio.into_iter().map(|r| 100. * r / sum).collect()
 }
 
 impl ToHtml for Content {
@@ -86,15 +193,22 @@ impl ToHtml for Content {
             include,
             include_html,
             math,
+            code,
+            table,
+            chart,
+            dot,
             img,
             video,
             iframe,
             lay_img,
+            blocks,
             mut frag,
             h_stack,
             v_stack,
             h_stack_border,
             v_stack_border,
+            grid,
+            ratio: _,
         } = self;
         frag.with_counter(ctx.frag.clone());
         let mut s = String::new();
@@ -107,25 +221,40 @@ impl ToHtml for Content {
                     .wrap("<h2 class=\"r-fit-text\">", "</h2>\n");
             }
         }
-        s += &frag.wrap("doc", &md2html(&doc));
+        s += &frag.wrap_with("doc", || md2html(&doc, ctx));
         if !include.is_empty() {
-            let doc = std::fs::read_to_string(include).unwrap();
-            let doc = if include_html { doc } else { md2html(&doc) };
+            let doc = ctx.read(include).unwrap_or_default();
+            let doc = if include_html {
+                doc
+            } else {
+                md2html(&doc, ctx)
+            };
             s += &frag.wrap("include", &doc);
         }
-        s += &frag.wrap("math", &math.wrap("\\[", "\\]"));
+        s += &frag.wrap_with("math", || ctx.math.render(&math, true, ctx));
+        s += &frag.wrap_with("code", || code.to_html(ctx));
+        s += &frag.wrap_with("table", || table.to_html(ctx));
+        s += &frag.wrap_with("chart", || chart.to_html(ctx));
+        s += &frag.wrap_with("dot", || dot.to_html(ctx));
         for media in [img.to_html(ctx), video.to_html(ctx), iframe.to_html(ctx)] {
             s += &media.wrap("<div class=\"h-stack\">\n", "</div>\n");
         }
         s += &lay_img
             .to_html(ctx)
             .wrap("<div class=\"r-stack\">", "</div>\n");
+        s += &blocks
+            .into_iter()
+            .map(|c| c.to_html(ctx))
+            .collect::<String>();
         if !h_stack.is_empty() {
-            let width = 100. / h_stack.len() as f32;
-            let pre = format!("<div style=\"width:{width:.04}%\">");
+            let widths = widths(&h_stack, ctx);
             s += &h_stack
                 .into_iter()
-                .map(|c| c.to_html(ctx).wrap(&pre, "</div>\n"))
+                .zip(widths)
+                .map(|(c, width)| {
+                    let pre = format!("<div style=\"width:{width:.04}%\">");
+                    c.to_html(ctx).wrap(&pre, "</div>\n")
+                })
                 .collect::<String>()
                 .wrap("<div class=\"h-stack\">", "</div>\n");
         }
@@ -135,16 +264,18 @@ impl ToHtml for Content {
             .collect::<String>()
             .wrap("<div class=\"v-stack\">", "</div>\n");
         if !h_stack_border.is_empty() {
-            let width = 100. / h_stack_border.len() as f32;
-            let pre = format!("<div class=\"h-stack-border\" style=\"width:{width:.04}%\">");
+            let widths = widths(&h_stack_border, ctx);
             s += &h_stack_border
                 .into_iter()
+                .zip(widths)
                 .enumerate()
-                .map(|(i, c)| {
+                .map(|(i, (c, width))| {
                     let text = c.to_html(ctx);
                     if i == 0 {
                         text.wrap(&format!("<div style=\"width:{width:.04}%\">"), "</div>")
                     } else {
+                        let pre =
+                            format!("<div class=\"h-stack-border\" style=\"width:{width:.04}%\">");
                         text.wrap(&pre, "</div>")
                     }
                 })
@@ -164,6 +295,7 @@ impl ToHtml for Content {
             })
             .collect::<String>()
             .wrap("<div class=\"v-stack\">", "</div>\n");
+        s += &grid.to_html(ctx);
         s
     }
 }
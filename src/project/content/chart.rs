@@ -0,0 +1,388 @@
+use super::*;
+use std::fmt::Write;
+
+/// Default series colors, only the first one follows the theme.
+const PALETTE: &[&str] = &[
+    "var(--r-link-color)",
+    "#e6550d",
+    "#31a354",
+    "#756bb1",
+    "#de2d26",
+    "#636363",
+    "#3182bd",
+    "#fd8d3c",
+];
+const MARGIN: f64 = 20.;
+const AXIS: f64 = 50.;
+const LEGEND: f64 = 140.;
+
+/// Nice ticks of a range.
+fn ticks(min: f64, max: f64) -> Vec<f64> {
+    let (min, max) = if min == max {
+        (min - 1., max + 1.)
+    } else {
+        (min, max)
+    };
+    let raw = (max - min) / 5.;
+    let mag = 10f64.powf(raw.log10().floor());
+    let step = [1., 2., 5., 10.]
+        .into_iter()
+        .map(|m| m * mag)
+        .find(|s| *s >= raw)
+        .unwrap_or(raw);
+    let start = (min / step).floor() as i64;
+    let end = (max / step).ceil() as i64;
+    (start..=end).map(|i| i as f64 * step).collect()
+}
+
+/// Round the coordinate to keep the SVG short.
+fn round(n: f64) -> f64 {
+    (n * 100.).round() / 100.
+}
+
+fn fmt_num(n: f64) -> String {
+    let s = format!("{n:.6}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Chart types.
+#[derive(Default, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartType {
+    /// Bar chart.
+    #[default]
+    Bar,
+    /// Line chart.
+    Line,
+    /// Scatter chart.
+    Scatter,
+}
+
+/// A data series of [`Chart`].
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct Series {
+    /// Series name, shown in the legend.
+    pub name: String,
+    /// Y values.
+    pub data: Vec<f64>,
+    /// X values of the scatter chart, defaults to the indexes.
+    pub x: Vec<f64>,
+    /// CSS color, defaults to a fixed palette, where only the first color
+    /// follows the link color of the theme.
+    pub color: String,
+}
+
+/// Build-time SVG chart, no JavaScript required.
+///
+/// ```yaml
+/// chart:
+///   type: bar
+///   labels: [A, B, C]
+///   y-label: Time (s)
+///   series:
+///     - name: Before
+///       data: [3, 5, 2]
+///     - name: After
+///       data: [1, 2, 1]
+/// ```
+///
+/// Or load the data from a CSV file, the first column is the labels (or the x
+/// values of the scatter chart), and the other columns are the series.
+///
+/// ```yaml
+/// chart:
+///   type: line
+///   src: data/bench.csv
+/// ```
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct Chart {
+    /// Chart type, `bar`, `line` or `scatter`.
+    pub r#type: ChartType,
+    /// The data series.
+    pub series: Vec<Series>,
+    /// The labels of the x axis, for bar chart and line chart.
+    pub labels: Vec<String>,
+    /// Load the labels and the series from a CSV file.
+    pub src: String,
+    /// X axis title.
+    #[serde(rename = "x-label")]
+    pub x_label: String,
+    /// Y axis title.
+    #[serde(rename = "y-label")]
+    pub y_label: String,
+    /// Show the legend, boolean `true` by default.
+    pub legend: bool,
+    /// SVG width, defaults to 640.
+    pub width: u32,
+    /// SVG height, defaults to 400.
+    pub height: u32,
+}
+
+impl Default for Chart {
+    fn default() -> Self {
+        Self {
+            r#type: ChartType::default(),
+            series: Vec::new(),
+            labels: Vec::new(),
+            src: String::new(),
+            x_label: String::new(),
+            y_label: String::new(),
+            legend: true,
+            width: 640,
+            height: 400,
+        }
+    }
+}
+
+impl Chart {
+    fn load_csv(&mut self, ctx: &Ctx) -> Option<()> {
+        let doc = ctx.read(&self.src)?;
+        let delimiter = if self.src.ends_with(".tsv") {
+            b'\t'
+        } else {
+            b','
+        };
+        let mut r = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .from_reader(doc.as_bytes());
+        let header = r
+            .headers()
+            .map_err(|e| ctx.error(format!("{}: {e}", self.src)))
+            .ok()?
+            .clone();
+        let mut series = header
+            .iter()
+            .skip(1)
+            .map(|name| Series { name: name.trim().to_string(), ..Series::default() })
+            .collect::<Vec<_>>();
+        let mut x = Vec::new();
+        for record in r.records() {
+            let record = record
+                .map_err(|e| ctx.error(format!("{}: {e}", self.src)))
+                .ok()?;
+            let mut it = record.iter().map(str::trim);
+            let label = it.next().unwrap_or_default();
+            self.labels.push(label.to_string());
+            x.push(label.parse().unwrap_or_default());
+            for (s, v) in series.iter_mut().zip(it) {
+                match v.parse::<f64>() {
+                    Ok(v) if v.is_finite() => s.data.push(v),
+                    _ => {
+                        ctx.error(format!("{}: `{v}` is not a number", self.src));
+                        return None;
+                    }
+                }
+            }
+        }
+        if self.r#type == ChartType::Scatter {
+            for s in &mut series {
+                s.x = x.clone();
+            }
+        }
+        self.series.extend(series);
+        Some(())
+    }
+}
+
+impl ToHtml for Chart {
+    fn to_html(mut self, ctx: &Ctx) -> String {
+        if !self.src.is_empty() && self.load_csv(ctx).is_none() {
+            return String::new();
+        }
+        let Self {
+            r#type,
+            series,
+            labels,
+            src: _,
+            x_label,
+            y_label,
+            legend,
+            width,
+            height,
+        } = self;
+        if series.iter().all(|s| s.data.is_empty()) {
+            ctx.error("chart has no data");
+            return String::new();
+        }
+        if let Some(se) = series
+            .iter()
+            .find(|s| !s.data.iter().chain(&s.x).all(|n| n.is_finite()))
+        {
+            ctx.error(format!("series `{}` has a non-finite number", se.name));
+            return String::new();
+        }
+        let (w, h) = (width as f64, height as f64);
+        let left = MARGIN + AXIS + if y_label.is_empty() { 0. } else { 20. };
+        let right = w - MARGIN - if legend { LEGEND } else { 0. };
+        let top = MARGIN;
+        let bottom = h - AXIS - if x_label.is_empty() { 0. } else { 20. };
+        let values = series.iter().flat_map(|s| s.data.iter().copied());
+        let y_min = values.clone().fold(f64::INFINITY, f64::min);
+        let y_max = values.fold(f64::NEG_INFINITY, f64::max);
+        let y_ticks = if r#type == ChartType::Bar {
+            ticks(y_min.min(0.), y_max.max(0.))
+        } else {
+            ticks(y_min, y_max)
+        };
+        let (y0, y1) = (y_ticks[0], y_ticks[y_ticks.len() - 1]);
+        let sy = |y: f64| round(bottom - (y - y0) / (y1 - y0) * (bottom - top));
+        let n = series
+            .iter()
+            .map(|s| s.data.len())
+            .max()
+            .unwrap_or_default();
+        let band = (right - left) / n.max(1) as f64;
+        let mut s = format!(
+            "<svg class=\"chart\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\" xmlns=\"http://www.w3.org/2000/svg\" \
+             font-size=\"14\" fill=\"currentColor\" stroke-width=\"2\">\n"
+        );
+        // Y axis
+        for y in &y_ticks {
+            let py = sy(*y);
+            writeln!(
+                s,
+                "<line x1=\"{left}\" y1=\"{py}\" x2=\"{right}\" y2=\"{py}\" \
+                 stroke=\"currentColor\" stroke-opacity=\"0.2\" stroke-width=\"1\"/>\
+                 <text x=\"{}\" y=\"{py}\" text-anchor=\"end\" dominant-baseline=\"middle\">{}</text>",
+                left - 8.,
+                fmt_num(*y)
+            )
+            .unwrap();
+        }
+        // X axis
+        let mut scatter_x = None;
+        if r#type == ChartType::Scatter {
+            let xs = series
+                .iter()
+                .flat_map(|s| (0..s.data.len()).map(|i| s.x.get(i).copied().unwrap_or(i as f64)));
+            let x_min = xs.clone().fold(f64::INFINITY, f64::min);
+            let x_max = xs.fold(f64::NEG_INFINITY, f64::max);
+            let x_ticks = ticks(x_min, x_max);
+            let (x0, x1) = (x_ticks[0], x_ticks[x_ticks.len() - 1]);
+            for x in &x_ticks {
+                let px = round(left + (x - x0) / (x1 - x0) * (right - left));
+                writeln!(
+                    s,
+                    "<text x=\"{px}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                    bottom + 20.,
+                    fmt_num(*x)
+                )
+                .unwrap();
+            }
+            scatter_x = Some((x0, x1));
+        } else {
+            for (i, label) in labels.iter().enumerate().take(n) {
+                let px = round(left + band * (i as f64 + 0.5));
+                writeln!(
+                    s,
+                    "<text x=\"{px}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                    bottom + 20.,
+                    html_escape(label)
+                )
+                .unwrap();
+            }
+        }
+        writeln!(
+            s,
+            "<line x1=\"{left}\" y1=\"{bottom}\" x2=\"{right}\" y2=\"{bottom}\" stroke=\"currentColor\"/>\
+             <line x1=\"{left}\" y1=\"{top}\" x2=\"{left}\" y2=\"{bottom}\" stroke=\"currentColor\"/>"
+        )
+        .unwrap();
+        if !x_label.is_empty() {
+            writeln!(
+                s,
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                (left + right) / 2.,
+                h - MARGIN,
+                html_escape(&x_label)
+            )
+            .unwrap();
+        }
+        if !y_label.is_empty() {
+            let (x, y) = (MARGIN, (top + bottom) / 2.);
+            writeln!(
+                s,
+                "<text x=\"{x}\" y=\"{y}\" text-anchor=\"middle\" \
+                 transform=\"rotate(-90 {x} {y})\">{}</text>",
+                html_escape(&y_label)
+            )
+            .unwrap();
+        }
+        // Series
+        let bar_w = round(band * 0.8 / series.len() as f64);
+        for (k, se) in series.iter().enumerate() {
+            let color = if se.color.is_empty() {
+                PALETTE[k % PALETTE.len()].to_string()
+            } else {
+                html_escape(&se.color)
+            };
+            let points = se
+                .data
+                .iter()
+                .enumerate()
+                .map(|(i, y)| match scatter_x {
+                    Some((x0, x1)) => {
+                        let x = se.x.get(i).copied().unwrap_or(i as f64);
+                        (round(left + (x - x0) / (x1 - x0) * (right - left)), sy(*y))
+                    }
+                    None => (round(left + band * (i as f64 + 0.5)), sy(*y)),
+                })
+                .collect::<Vec<_>>();
+            match r#type {
+                ChartType::Bar => {
+                    for (i, y) in se.data.iter().enumerate() {
+                        let x = round(left + band * (i as f64 + 0.1) + bar_w * k as f64);
+                        let (py, base) = (sy(*y), sy(0f64.clamp(y0, y1)));
+                        writeln!(
+                            s,
+                            "<rect x=\"{x}\" y=\"{}\" width=\"{bar_w}\" height=\"{}\" \
+                             style=\"fill:{color}\"><title>{}</title></rect>",
+                            py.min(base),
+                            round((py - base).abs()),
+                            fmt_num(*y)
+                        )
+                        .unwrap();
+                    }
+                }
+                ChartType::Line => {
+                    let path = points
+                        .iter()
+                        .map(|(x, y)| format!("{x},{y}"))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    writeln!(
+                        s,
+                        "<polyline points=\"{path}\" fill=\"none\" style=\"stroke:{color}\"/>"
+                    )
+                    .unwrap();
+                }
+                ChartType::Scatter => (),
+            }
+            if r#type != ChartType::Bar {
+                for (x, y) in &points {
+                    writeln!(
+                        s,
+                        "<circle cx=\"{x}\" cy=\"{y}\" r=\"4\" style=\"fill:{color}\"/>"
+                    )
+                    .unwrap();
+                }
+            }
+            if legend {
+                let (x, y) = (right + MARGIN, top + 24. * k as f64);
+                writeln!(
+                    s,
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"14\" height=\"14\" style=\"fill:{color}\"/>\
+                     <text x=\"{}\" y=\"{}\" dominant-baseline=\"middle\">{}</text>",
+                    x + 20.,
+                    y + 7.,
+                    html_escape(&se.name)
+                )
+                .unwrap();
+            }
+        }
+        s + "</svg>\n"
+    }
+}
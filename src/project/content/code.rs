@@ -0,0 +1,158 @@
+use super::*;
+
+fn parse_range(lines: &str) -> Option<(usize, usize)> {
+    if lines.is_empty() {
+        return Some((1, usize::MAX));
+    }
+    let (start, end) = lines.split_once('-').unwrap_or((lines, lines));
+    let start = if start.trim().is_empty() {
+        1
+    } else {
+        start.trim().parse().ok()?
+    };
+    let end = if end.trim().is_empty() {
+        usize::MAX
+    } else {
+        end.trim().parse().ok()?
+    };
+    (start > 0 && start <= end).then_some((start, end))
+}
+
+/// Match the marker name after the tag, such as `ANCHOR: name`.
+fn is_marker(line: &str, tag: &str, name: &str) -> bool {
+    line.split_once(tag)
+        .and_then(|(_, s)| s.split_whitespace().next())
+        .is_some_and(|s| s == name)
+}
+
+/// Remove the common indentation.
+fn dedent(lines: &[&str]) -> Vec<String> {
+    let indent = lines
+        .iter()
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.len() - s.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    lines
+        .iter()
+        .map(|s| s.get(indent..).unwrap_or_default().to_string())
+        .collect()
+}
+
+/// Include source code from a file.
+///
+/// ```yaml
+/// code:
+///   src: src/main.rs
+///   region: main
+///   lines: 2-10
+///   highlight: 1-3|5|7-9
+/// ```
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct Code {
+    /// Source file path.
+    pub src: String,
+    /// Language name, defaults to the file extension.
+    pub lang: String,
+    /// Line range, such as `10-40`, `10-` and `-40`.
+    ///
+    /// If the region is specified, the range is counted in the region.
+    pub lines: String,
+    /// Region marker, the lines between `ANCHOR: name` and `ANCHOR_END: name`.
+    ///
+    /// The other marker lines will be removed, and the region must be closed.
+    pub region: String,
+    /// The starting line number, defaults to the first line of the range.
+    #[serde(rename = "start-from")]
+    pub start_from: Option<usize>,
+    /// Remove the common indentation.
+    pub trim: bool,
+    /// [Step highlights](https://revealjs.com/code/#step-by-step-highlights),
+    /// such as `1-3|5|7-9`. The brackets are optional.
+    pub highlight: String,
+}
+
+impl ToHtml for Code {
+    fn to_html(self, ctx: &Ctx) -> String {
+        let Self {
+            src,
+            lang,
+            lines,
+            region,
+            start_from,
+            trim,
+            highlight,
+        } = self;
+        let doc = match ctx.read(&src) {
+            Some(doc) => doc,
+            None => return String::new(),
+        };
+        let mut code = doc.lines().enumerate().collect::<Vec<_>>();
+        if !region.is_empty() {
+            match code
+                .iter()
+                .position(|(_, s)| is_marker(s, "ANCHOR: ", &region))
+            {
+                Some(i) => code.drain(..=i),
+                None => {
+                    ctx.error(format!("{src}: region `{region}` not found"));
+                    return String::new();
+                }
+            };
+            match code
+                .iter()
+                .position(|(_, s)| is_marker(s, "ANCHOR_END: ", &region))
+            {
+                Some(i) => code.truncate(i),
+                None => {
+                    ctx.error(format!("{src}: region `{region}` is not closed"));
+                    return String::new();
+                }
+            }
+        }
+        let (start, end) = match parse_range(&lines) {
+            Some(range) => range,
+            None => {
+                ctx.error(format!("{src}: invalid line range `{lines}`"));
+                return String::new();
+            }
+        };
+        if !lines.is_empty() && (start > code.len() || end != usize::MAX && end > code.len()) {
+            ctx.error(format!(
+                "{src}: line range `{lines}` is out of bounds, there are {} lines",
+                code.len()
+            ));
+            return String::new();
+        }
+        let code = code
+            .into_iter()
+            .skip(start - 1)
+            .take(end - start + 1)
+            .filter(|(_, s)| !s.contains("ANCHOR: ") && !s.contains("ANCHOR_END: "))
+            .collect::<Vec<_>>();
+        let start_from = start_from.unwrap_or_else(|| code.first().map_or(1, |(i, _)| i + 1));
+        let code = code.into_iter().map(|(_, s)| s).collect::<Vec<_>>();
+        let code = if trim {
+            dedent(&code).join("\n")
+        } else {
+            code.join("\n")
+        };
+        let lang = if lang.is_empty() {
+            std::path::Path::new(&src)
+                .extension()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        } else {
+            lang
+        };
+        let highlight = highlight.trim_start_matches('[').trim_end_matches(']');
+        let info = CodeInfo {
+            lang,
+            line: highlight.to_string(),
+            start_from: Some(start_from),
+            ..CodeInfo::default()
+        };
+        code_block(&code, &info, ctx)
+    }
+}
@@ -0,0 +1,530 @@
+use super::*;
+use layout::{
+    core::{
+        color::Color,
+        format::{ClipHandle, RenderBackend},
+        geometry::Point,
+        style::StyleAttr,
+    },
+    gv::{parser::ast, DotParser, GraphBuilder},
+};
+use std::{
+    cell::Cell,
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt::Write,
+    hash::{Hash, Hasher},
+    panic::{self, catch_unwind, AssertUnwindSafe},
+    sync::Once,
+};
+
+thread_local! {
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Map the default colors to the theme colors.
+fn color(c: &Color, default: &str) -> String {
+    match c.to_web_color().as_str() {
+        "#000000ff" => "currentColor".to_string(),
+        "#ffffffff" => default.to_string(),
+        c => c.to_string(),
+    }
+}
+
+fn fill(look: &StyleAttr) -> String {
+    match &look.fill_color {
+        Some(c) => color(c, "var(--r-background-color)"),
+        None => "none".to_string(),
+    }
+}
+
+/// Replace the first visible character with a private-use one, so the label
+/// keeps its size.
+fn tag(label: &str, i: usize) -> Option<String> {
+    let c = u32::try_from(i)
+        .ok()
+        .filter(|i| *i < 0xfffe)
+        .and_then(|i| char::from_u32(0xf0000 + i))?;
+    let mut done = false;
+    let tagged = label
+        .chars()
+        .map(|ch| match ch {
+            '\n' | '\r' => ch,
+            _ if done => ch,
+            _ => {
+                done = true;
+                c
+            }
+        })
+        .collect();
+    done.then_some(tagged)
+}
+
+/// The edges in the declaration order.
+///
+/// The edge labels are drawn as the nodes, so each label is tagged with its
+/// edge index.
+#[derive(Default)]
+struct Edges {
+    // Original labels by the tagged labels
+    labels: HashMap<String, (usize, String)>,
+    // Edge indexes in the drawing order, the self-loops are drawn last
+    order: Vec<usize>,
+    loops: Vec<usize>,
+}
+
+impl Edges {
+    /// Split the chained edges and tag their labels.
+    fn visit(&mut self, graph: &mut ast::Graph, scope: &mut Vec<Option<String>>) {
+        let label = |list: &ast::AttributeList| {
+            list.iter()
+                .rfind(|(k, _)| k == "label")
+                .map(|(_, v)| v.clone())
+        };
+        scope.push(None);
+        for stmt in std::mem::take(&mut graph.list.list) {
+            match stmt {
+                ast::Stmt::Edge(e) => {
+                    let text = label(&e.list)
+                        .or_else(|| scope.iter().rev().find_map(Clone::clone))
+                        .unwrap_or_default();
+                    let mut from = e.from.clone();
+                    for (to, kind) in e.to {
+                        let i = self.order.len() + self.loops.len();
+                        if from.name == to.name {
+                            self.loops.push(i);
+                        } else {
+                            self.order.push(i);
+                        }
+                        let mut edge = ast::EdgeStmt::new(from);
+                        edge.list = e.list.clone();
+                        if let Some(tagged) = tag(&text, i) {
+                            edge.list.add_attr("label", &tagged);
+                            self.labels.insert(tagged, (i, text.clone()));
+                        }
+                        from = ast::NodeId::new(&to.name, &e.from.port);
+                        edge.insert(to, kind);
+                        graph.list.list.push(ast::Stmt::Edge(edge));
+                    }
+                }
+                ast::Stmt::Attribute(a) => {
+                    if matches!(a.target, ast::AttrStmtTarget::Edge) {
+                        if let Some(text) = label(&a.list) {
+                            *scope.last_mut().unwrap() = Some(text);
+                        }
+                    }
+                    graph.list.list.push(ast::Stmt::Attribute(a));
+                }
+                ast::Stmt::SubGraph(mut g) => {
+                    self.visit(&mut g, scope);
+                    graph.list.list.push(ast::Stmt::SubGraph(g));
+                }
+                stmt => graph.list.list.push(stmt),
+            }
+        }
+        scope.pop();
+    }
+}
+
+/// SVG canvas that groups the drawings by nodes and edges.
+///
+/// The original nodes are drawn first, marked by their properties. Then the
+/// edges are drawn, and the edge labels are found by their tags.
+#[derive(Default)]
+struct Canvas {
+    id: String,
+    size: (f64, f64),
+    clips: String,
+    other: String,
+    nodes: Vec<String>,
+    // Whether the label of the current node is drawn
+    current: Option<bool>,
+    // Records are drawn without the properties
+    record: bool,
+    edge_list: Edges,
+    labels: Vec<(usize, String)>,
+    edges: Vec<String>,
+}
+
+impl Canvas {
+    fn grow(&mut self, p: Point) {
+        self.size.0 = self.size.0.max(p.x + 5.);
+        self.size.1 = self.size.1.max(p.y + 5.);
+    }
+
+    fn shape(&mut self, properties: Option<String>, svg: String) {
+        if properties.is_some() {
+            self.nodes.push(String::new());
+            self.current = Some(false);
+            self.record = false;
+        }
+        match self.current {
+            Some(false) => *self.nodes.last_mut().unwrap() += &svg,
+            _ => {
+                self.record = true;
+                self.other += &svg;
+            }
+        }
+    }
+
+    fn arrow_head(s: &mut String, ctrl: Point, p: Point, look: &StyleAttr) {
+        let (dx, dy) = (p.x - ctrl.x, p.y - ctrl.y);
+        let len = dx.hypot(dy);
+        if len == 0. {
+            return;
+        }
+        let (ux, uy) = (dx / len * 10., dy / len * 10.);
+        let (bx, by) = (p.x - ux, p.y - uy);
+        write!(
+            s,
+            "<polygon points=\"{},{} {},{} {},{}\" fill=\"{}\"/>",
+            p.x,
+            p.y,
+            bx - uy * 0.35,
+            by + ux * 0.35,
+            bx + uy * 0.35,
+            by - ux * 0.35,
+            color(&look.line_color, "currentColor"),
+        )
+        .unwrap();
+    }
+
+    /// Generate SVG, the nodes and the edges can be wrapped as fragments.
+    fn finish(self, node_frag: &Option<String>, edge_frag: &Option<String>, ctx: &Ctx) -> String {
+        let Self {
+            size: (w, h),
+            clips,
+            other,
+            nodes,
+            edge_list,
+            labels,
+            edges,
+            ..
+        } = self;
+        let mut s = format!(
+            "<svg class=\"dot\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" \
+             xmlns=\"http://www.w3.org/2000/svg\" fill=\"currentColor\">\n\
+             <defs>{clips}</defs>\n{other}"
+        );
+        let frag = |s: &mut String, frag: &Option<String>, svg: &str| match frag {
+            Some(frag) => {
+                let frag = html_escape(frag);
+                let i = ctx.frag.get();
+                ctx.frag.set(i + 1);
+                writeln!(
+                    s,
+                    "<g class=\"fragment {frag}\" data-fragment-index=\"{i}\">{svg}</g>"
+                )
+                .unwrap();
+            }
+            None => *s += svg,
+        };
+        for node in &nodes {
+            frag(&mut s, node_frag, node);
+        }
+        // Edge labels are revealed with their edges in the declaration order
+        let Edges { order, loops, .. } = edge_list;
+        let mut list = vec![String::new(); order.len() + loops.len()];
+        for (i, edge) in order.into_iter().chain(loops).zip(edges) {
+            list[i] += &edge;
+        }
+        for (i, label) in labels {
+            list[i] += &label;
+        }
+        for edge in list.iter().filter(|s| !s.is_empty()) {
+            frag(&mut s, edge_frag, edge);
+        }
+        s + "</svg>\n"
+    }
+}
+
+impl RenderBackend for Canvas {
+    fn draw_rect(
+        &mut self,
+        xy: Point,
+        size: Point,
+        look: &StyleAttr,
+        properties: Option<String>,
+        clip: Option<ClipHandle>,
+    ) {
+        self.grow(Point::new(xy.x + size.x, xy.y + size.y));
+        let clip = match clip {
+            Some(clip) => format!(" clip-path=\"url(#{}c{clip})\"", self.id),
+            None => String::new(),
+        };
+        let svg = format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\" \
+             stroke=\"{}\" stroke-width=\"{}\"{clip}/>",
+            xy.x,
+            xy.y,
+            size.x,
+            size.y,
+            look.rounded,
+            fill(look),
+            color(&look.line_color, "currentColor"),
+            look.line_width,
+        );
+        self.shape(properties, svg);
+    }
+
+    fn draw_line(
+        &mut self,
+        start: Point,
+        stop: Point,
+        look: &StyleAttr,
+        properties: Option<String>,
+    ) {
+        let svg = format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+            start.x,
+            start.y,
+            stop.x,
+            stop.y,
+            color(&look.line_color, "currentColor"),
+            look.line_width,
+        );
+        self.shape(properties, svg);
+    }
+
+    fn draw_circle(
+        &mut self,
+        xy: Point,
+        size: Point,
+        look: &StyleAttr,
+        properties: Option<String>,
+    ) {
+        self.grow(Point::new(xy.x + size.x / 2., xy.y + size.y / 2.));
+        let svg = format!(
+            "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" fill=\"{}\" stroke=\"{}\" \
+             stroke-width=\"{}\"/>",
+            xy.x,
+            xy.y,
+            size.x / 2.,
+            size.y / 2.,
+            fill(look),
+            color(&look.line_color, "currentColor"),
+            look.line_width,
+        );
+        self.shape(properties, svg);
+    }
+
+    fn draw_text(&mut self, xy: Point, text: &str, look: &StyleAttr) {
+        let (edge, text) = match self.edge_list.labels.get(text) {
+            Some((i, label)) => (Some(*i), label.clone()),
+            None => (None, text.to_string()),
+        };
+        let lines = text.lines().count();
+        let mut svg = format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" \
+             dominant-baseline=\"middle\">",
+            xy.x,
+            xy.y - (lines as f64 + 1.) * look.font_size as f64 / 2.,
+            look.font_size,
+        );
+        for line in text.lines() {
+            write!(
+                svg,
+                "<tspan x=\"{}\" dy=\"1em\">{}</tspan>",
+                xy.x,
+                html_escape(line)
+            )
+            .unwrap();
+        }
+        svg += "</text>";
+        match (edge, &mut self.current) {
+            (Some(i), _) => self.labels.push((i, svg)),
+            (None, Some(done @ false)) => {
+                *done = true;
+                *self.nodes.last_mut().unwrap() += &svg;
+            }
+            _ => self.other += &svg,
+        }
+    }
+
+    fn draw_arrow(
+        &mut self,
+        path: &[(Point, Point)],
+        dashed: bool,
+        head: (bool, bool),
+        look: &StyleAttr,
+        _properties: Option<String>,
+        text: &str,
+    ) {
+        self.current = None;
+        let mut d = format!(
+            "M {} {} C {} {}, {} {}, {} {}",
+            path[0].0.x,
+            path[0].0.y,
+            path[0].1.x,
+            path[0].1.y,
+            path[1].0.x,
+            path[1].0.y,
+            path[1].1.x,
+            path[1].1.y
+        );
+        for (c, p) in &path[2..] {
+            write!(d, " S {} {}, {} {}", c.x, c.y, p.x, p.y).unwrap();
+        }
+        let mut svg = format!(
+            "<path d=\"{d}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"{}/>",
+            color(&look.line_color, "currentColor"),
+            look.line_width,
+            if dashed {
+                " stroke-dasharray=\"5,5\""
+            } else {
+                ""
+            },
+        );
+        if head.0 {
+            Self::arrow_head(&mut svg, path[0].1, path[0].0, look);
+        }
+        if head.1 {
+            let (c, p) = path[path.len() - 1];
+            Self::arrow_head(&mut svg, c, p, look);
+        }
+        if !text.is_empty() {
+            let (c, p) = path[path.len() / 2];
+            write!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\">{}</text>",
+                (c.x + p.x) / 2.,
+                (c.y + p.y) / 2.,
+                look.font_size,
+                html_escape(text)
+            )
+            .unwrap();
+        }
+        for (c, p) in path {
+            self.grow(*c);
+            self.grow(*p);
+        }
+        self.edges.push(svg);
+    }
+
+    fn create_clip(&mut self, xy: Point, size: Point, rounded_px: usize) -> ClipHandle {
+        let handle = self.clips.matches("<clipPath").count();
+        write!(
+            self.clips,
+            "<clipPath id=\"{}c{handle}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+             rx=\"{rounded_px}\"/></clipPath>",
+            self.id, xy.x, xy.y, size.x, size.y
+        )
+        .unwrap();
+        handle
+    }
+}
+
+/// Run the closure without printing the panic message, the layout engine
+/// asserts on the unsupported graphs.
+///
+/// The panic hook is installed once, and only keeps quiet on the threads
+/// running this function.
+fn quiet<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.get() {
+                hook(info);
+            }
+        }));
+    });
+    QUIET.set(true);
+    let r = catch_unwind(AssertUnwindSafe(f));
+    QUIET.set(false);
+    r.map_err(|e| {
+        e.downcast_ref::<String>()
+            .cloned()
+            .or_else(|| e.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "unknown error".to_string())
+    })
+}
+
+/// Lay out a Graphviz DOT graph into SVG.
+pub(crate) fn dot2svg(
+    graph: &str,
+    node_frag: &Option<String>,
+    edge_frag: &Option<String>,
+    ctx: &Ctx,
+) -> Result<String, String> {
+    let mut parser = DotParser::new(graph);
+    let mut ast = match quiet(|| parser.process()) {
+        Ok(Ok(ast)) => ast,
+        Ok(Err(e)) | Err(e) => return Err(format!("invalid DOT graph: {e}")),
+    };
+    let mut edge_list = Edges::default();
+    edge_list.visit(&mut ast, &mut Vec::new());
+    let mut hasher = DefaultHasher::new();
+    graph.hash(&mut hasher);
+    let id = format!("dot{:x}", hasher.finish() & 0xffffff);
+    quiet(move || {
+        let mut builder = GraphBuilder::new();
+        builder.visit_graph(&ast);
+        let mut vg = builder.get();
+        for n in vg.iter_nodes() {
+            vg.element_mut(n).properties = Some(String::new());
+        }
+        let mut canvas = Canvas { id, edge_list, ..Canvas::default() };
+        if vg.num_nodes() > 0 {
+            vg.do_it(false, false, false, &mut canvas);
+        }
+        canvas
+    })
+    .map(|canvas| canvas.finish(node_frag, edge_frag, ctx))
+    .map_err(|msg| format!("failed to lay out DOT graph: {msg}"))
+}
+
+/// [Graphviz DOT](https://graphviz.org/doc/info/lang.html) graph, laid out
+/// as an inline SVG at build time.
+///
+/// ```yaml
+/// dot:
+///   graph: |
+///     digraph {
+///       a -> b -> c;
+///       a -> c [label="skip"];
+///     }
+///   node-frag: fade-in
+/// ```
+///
+/// The fenced code blocks in Markdown with the `dot` language are also laid
+/// out.
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct Dot {
+    /// Load the graph from a file.
+    pub src: String,
+    /// Inline graph.
+    pub graph: String,
+    /// Reveal the nodes as fragments in the declaration order, the value is
+    /// the fragment style.
+    #[serde(rename = "node-frag")]
+    pub node_frag: Option<String>,
+    /// Reveal the edges as fragments in the declaration order, after the
+    /// nodes. The value is the fragment style.
+    #[serde(rename = "edge-frag")]
+    pub edge_frag: Option<String>,
+}
+
+impl ToHtml for Dot {
+    fn to_html(self, ctx: &Ctx) -> String {
+        let Self { src, graph, node_frag, edge_frag } = self;
+        let graph = if src.is_empty() {
+            graph
+        } else {
+            match ctx.read(&src) {
+                Some(graph) => graph,
+                None => return String::new(),
+            }
+        };
+        if graph.trim().is_empty() {
+            return String::new();
+        }
+        dot2svg(&graph, &node_frag, &edge_frag, ctx).unwrap_or_else(|e| {
+            match src.as_str() {
+                "" => ctx.error(e),
+                src => ctx.error(format!("{src}: {e}")),
+            }
+            String::new()
+        })
+    }
+}
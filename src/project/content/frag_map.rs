@@ -5,26 +5,32 @@ use std::{cell::Cell, collections::HashMap, fmt::Write, rc::Rc};
 /// + The index are the `data-fragment-index`.
 /// + Block are **content**, but exclude stacks (sub-contents).
 /// + Stacks can have local fragment option, but still ordered.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct FragMap {
     #[serde(skip)]
-    counter: Rc<Cell<u8>>,
+    counter: Rc<Cell<u32>>,
     /// Fragment data structure.
     pub frag: Vec<HashMap<String, Option<String>>>,
 }
 
 impl FragMap {
     /// Set the counter.
-    pub fn with_counter(&mut self, counter: Rc<Cell<u8>>) {
+    pub fn with_counter(&mut self, counter: Rc<Cell<u32>>) {
         self.counter = counter;
     }
 
     /// Wrap inner text with fragment options.
     pub fn wrap(&self, tag: &str, text: &str) -> String {
-        if text.is_empty() {
-            return String::new();
-        }
+        self.wrap_with(tag, || text.to_string())
+    }
+
+    /// Wrap the rendered text with fragment options.
+    ///
+    /// The indexes of the wrapper are taken before rendering, so the inner
+    /// fragments are shown after the wrapper.
+    pub fn wrap_with(&self, tag: &str, render: impl FnOnce() -> String) -> String {
+        let start = self.counter.get();
         let mut head = String::new();
         let mut end = String::new();
         for map in &self.frag {
@@ -40,6 +46,11 @@ impl FragMap {
                 self.counter.set(i + 1);
             }
         }
-        head + text + &end
+        let text = render();
+        if text.is_empty() {
+            self.counter.set(start);
+            return String::new();
+        }
+        head + &text + &end
     }
 }
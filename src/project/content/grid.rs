@@ -0,0 +1,101 @@
+use super::*;
+use yaml_peg::serde::Stringify;
+
+fn track(size: &Stringify) -> String {
+    match size {
+        Stringify::Int(n) => format!("{n}fr"),
+        Stringify::Float(n) => format!("{n}fr"),
+        size => size.to_string(),
+    }
+}
+
+/// [CSS grid](https://developer.mozilla.org/en-US/docs/Web/CSS/CSS_Grid_Layout)
+/// layout.
+///
+/// ```yaml
+/// grid:
+///   cols: [2, 1]
+///   gap: 20px
+///   areas:
+///     - main side
+///     - main foot
+///   cells:
+///     - area: main
+///       doc: Main
+///     - area: side
+///       doc: Side
+///     - area: foot
+///       doc: Foot
+/// ```
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct Grid {
+    /// Column sizes. Numbers are ratios (`fr` unit), and strings are CSS
+    /// sizes, such as `200px` and `auto`.
+    #[schemars(with = "Vec<schema::Stringify>")]
+    pub cols: Vec<Stringify>,
+    /// Row sizes, same as `cols`.
+    #[schemars(with = "Vec<schema::Stringify>")]
+    pub rows: Vec<Stringify>,
+    /// Gap between the cells, CSS size.
+    #[schemars(with = "schema::Stringify")]
+    pub gap: Stringify,
+    /// Vertical alignment of the cells (`align-items`), such as `center`.
+    pub align: String,
+    /// Horizontal alignment of the cells (`justify-items`), such as `center`.
+    pub justify: String,
+    /// Named areas, one string per row.
+    pub areas: Vec<String>,
+    /// The cells of the grid.
+    pub cells: Vec<GridCell>,
+}
+
+impl ToHtml for Grid {
+    fn to_html(self, ctx: &Ctx) -> String {
+        let Self { cols, rows, gap, align, justify, areas, cells } = self;
+        if cells.is_empty() {
+            return String::new();
+        }
+        let join = |sizes: &[Stringify]| sizes.iter().map(track).collect::<Vec<_>>().join(" ");
+        let areas = areas
+            .iter()
+            .map(|row| format!("'{row}'"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let style = "display:grid;".to_string()
+            + &join(&cols).wrap("grid-template-columns:", ";")
+            + &join(&rows).wrap("grid-template-rows:", ";")
+            + &areas.wrap("grid-template-areas:", ";")
+            + &gap.to_string().wrap("gap:", ";")
+            + &align.wrap("align-items:", ";")
+            + &justify.wrap("justify-items:", ";");
+        let style = html_escape(&style);
+        cells
+            .into_iter()
+            .map(|c| c.to_html(ctx))
+            .collect::<String>()
+            .wrap(
+                &format!("<div class=\"grid\" style=\"{style}\">\n"),
+                "</div>\n",
+            )
+    }
+}
+
+/// The cell of [`Grid`].
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct GridCell {
+    /// The area name of this cell.
+    pub area: String,
+    /// Cells have all attributes of "content"s. (*flatten*)
+    #[serde(flatten)]
+    pub content: Content,
+}
+
+impl ToHtml for GridCell {
+    fn to_html(self, ctx: &Ctx) -> String {
+        let Self { area, content } = self;
+        let style = html_escape(&area).wrap(" style=\"grid-area:", "\"");
+        format!("<div{style}>{}</div>\n", content.to_html(ctx))
+    }
+}
@@ -0,0 +1,121 @@
+use std::{path::Path, sync::LazyLock};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Color, Theme, ThemeSet},
+    html::{styled_line_to_highlighted_html, IncludeBackground},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+/// The highlight.js themes and the similar built-in themes.
+const THEMES: &[(&str, &str)] = &[
+    ("zenburn", "base16-mocha.dark"),
+    ("monokai", "base16-eighties.dark"),
+    ("monokai-sublime", "base16-eighties.dark"),
+    ("default", "InspiredGitHub"),
+    ("github", "InspiredGitHub"),
+    ("solarized-dark", "Solarized (dark)"),
+    ("solarized-light", "Solarized (light)"),
+];
+
+/// The built-in themes and syntaxes, loaded once.
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+fn css_color(c: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b)
+}
+
+/// Code highlighters.
+#[derive(Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+pub enum CodeHighlighter {
+    /// The highlight.js plugin in the browser, the default highlighter.
+    #[default]
+    #[serde(rename = "highlight.js")]
+    HighlightJs,
+    /// Build-time highlighter.
+    #[serde(rename = "syntect")]
+    Syntect,
+}
+
+/// Build-time highlighter, powered by [syntect](https://github.com/trishume/syntect).
+pub struct Syntect {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+}
+
+impl Syntect {
+    /// Load the theme and the extra syntax definitions.
+    ///
+    /// The theme can be a highlight.js theme name which has a similar built-in
+    /// theme, a built-in theme name, or a `.tmTheme` file path. The paths are
+    /// relative to the project directory `base`.
+    pub fn new(code_theme: &str, syntaxes: &str, base: &Path) -> Result<Self, String> {
+        let theme = if code_theme.ends_with(".tmTheme") {
+            ThemeSet::get_theme(base.join(code_theme)).map_err(|e| format!("{code_theme}: {e}"))?
+        } else {
+            let name = THEMES
+                .iter()
+                .find(|(hljs, _)| *hljs == code_theme)
+                .map_or(code_theme, |(_, name)| name);
+            let themes = &THEME_SET.themes;
+            match themes.get(name) {
+                Some(theme) => theme.clone(),
+                None => {
+                    let mut names = THEMES.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+                    names.extend(themes.keys().map(String::as_str));
+                    return Err(format!(
+                        "unknown code theme `{code_theme}` for syntect, \
+                         use a .tmTheme file or one of: {}",
+                        names.join(", ")
+                    ));
+                }
+            }
+        };
+        let syntaxes = if syntaxes.is_empty() {
+            SYNTAX_SET.clone()
+        } else {
+            let mut builder = SYNTAX_SET.clone().into_builder();
+            builder
+                .add_from_folder(base.join(syntaxes), true)
+                .map_err(|e| format!("{syntaxes}: {e}"))?;
+            builder.build()
+        };
+        Ok(Self { syntaxes, theme })
+    }
+
+    /// The style of the code block.
+    pub(crate) fn style(&self) -> String {
+        let settings = &self.theme.settings;
+        let mut style = String::new();
+        if let Some(c) = settings.background {
+            style += &format!("background-color:{};", css_color(c));
+        }
+        if let Some(c) = settings.foreground {
+            style += &format!("color:{};", css_color(c));
+        }
+        style
+    }
+
+    /// Highlight the code into HTML, the unknown language is treated as plain
+    /// text.
+    pub(crate) fn highlight(&self, code: &str, lang: &str) -> String {
+        let syntax = self
+            .syntaxes
+            .find_syntax_by_token(lang)
+            .unwrap_or_else(|| self.syntaxes.find_syntax_plain_text());
+        let mut h = HighlightLines::new(syntax, &self.theme);
+        let mut s = String::with_capacity(code.len() * 2);
+        for line in LinesWithEndings::from(code) {
+            // Every line is closed, so the line numbers can be split
+            let html = h
+                .highlight_line(line, &self.syntaxes)
+                .and_then(|ranges| styled_line_to_highlighted_html(&ranges, IncludeBackground::No));
+            match html {
+                Ok(html) => s += &html,
+                Err(_) => pulldown_cmark_escape::escape_html(&mut s, line).unwrap(),
+            }
+        }
+        s
+    }
+}
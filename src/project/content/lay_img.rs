@@ -1,7 +1,7 @@
 use super::*;
 
 /// [Layout stack](https://revealjs.com/layout/#stack) for images.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct LayImg {
     /// The animation option. Independent from `FragMap` option.
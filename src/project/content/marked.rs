@@ -1,42 +1,329 @@
-use pulldown_cmark::{html::push_html, CodeBlockKind, Event, Options, Parser, Tag};
+use super::{dot2svg, extract_math, html_escape, mark_refs, restore_math, Ctx, MathRenderer};
+use pulldown_cmark::{html::push_html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use pulldown_cmark_escape::escape_html;
 use std::fmt::Write;
 
-const MARKED: Options = {
-    let bits = Options::ENABLE_TABLES.bits()
-        | Options::ENABLE_SMART_PUNCTUATION.bits()
-        | Options::ENABLE_TASKLISTS.bits()
-        | Options::ENABLE_STRIKETHROUGH.bits();
-    Options::from_bits_truncate(bits)
-};
-
-fn marked(e: Event) -> Event {
-    match e {
-        // Support line number for code block
-        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
-            let info = info.replace(' ', "");
-            let mut head = String::new();
-            if info.is_empty() {
-                head += "<pre><code>"
-            } else {
-                let lang = info.split('[').next().unwrap();
-                let line = info
-                    .replace(lang, "")
-                    .replace(|s| (s == '[') | (s == ']'), "");
-                write!(head, "<pre><code class=\"language-{lang}\"").unwrap();
-                if !line.is_empty() {
-                    write!(head, " data-line-numbers=\"{line}\"").unwrap();
+/// Markdown extensions.
+///
+/// ```yaml
+/// markdown:
+///   footnotes: true
+///   smart-punctuation: false
+/// ```
+///
+/// The options apply to all Markdown text, including the titles, the notes
+/// and the included files. The tables, task lists and strikethrough are
+/// always enabled.
+#[derive(Clone, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct MarkdownOption {
+    /// Footnotes `[^1]`, placed at the end of each Markdown block.
+    pub footnotes: bool,
+    /// Heading attributes `# Title {#id .class}`.
+    #[serde(rename = "heading-attributes")]
+    pub heading_attributes: bool,
+    /// Definition lists.
+    #[serde(rename = "definition-list")]
+    pub definition_list: bool,
+    /// Pass the `$...$` / `$$...$$` math to the math renderer without
+    /// Markdown escaping, so the star * symbol needs no backslash.
+    ///
+    /// Otherwise, the dollar signs are the plain text for the client-side
+    /// renderers.
+    pub math: bool,
+    /// Remove the raw HTML.
+    #[serde(rename = "strip-html")]
+    pub strip_html: bool,
+    /// Smart punctuation, `true` by default. Disable it to keep the straight
+    /// quotes and the dashes.
+    #[serde(rename = "smart-punctuation")]
+    pub smart_punctuation: bool,
+}
+
+impl Default for MarkdownOption {
+    fn default() -> Self {
+        Self {
+            footnotes: false,
+            heading_attributes: false,
+            definition_list: false,
+            math: false,
+            strip_html: false,
+            smart_punctuation: true,
+        }
+    }
+}
+
+impl MarkdownOption {
+    fn options(&self) -> Options {
+        let mut opts =
+            Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+        for (enable, opt) in [
+            (self.footnotes, Options::ENABLE_FOOTNOTES),
+            (self.heading_attributes, Options::ENABLE_HEADING_ATTRIBUTES),
+            (self.definition_list, Options::ENABLE_DEFINITION_LIST),
+            (self.math, Options::ENABLE_MATH),
+            (self.smart_punctuation, Options::ENABLE_SMART_PUNCTUATION),
+        ] {
+            opts.set(opt, enable);
+        }
+        opts
+    }
+}
+
+/// The attributes of a code block, parsed from the info string of the fenced
+/// code block.
+///
+/// ````markdown
+/// ```rust [1-3|5] {start=10 trim noescape .my-class #my-id file=src/main.rs}
+/// ```
+/// ````
+///
+/// + `[...]` is the step highlights.
+/// + `start=n` is the starting line number.
+/// + `trim` and `noescape` flags are the `data-trim` and `data-noescape`
+///   options of Reveal.js.
+/// + `.name` and `#name` are the classes and the id of the block.
+/// + `file=name` shows the file name as the caption, use quotes for the name
+///   with spaces.
+#[derive(Default)]
+pub(crate) struct CodeInfo {
+    pub(crate) lang: String,
+    pub(crate) line: String,
+    pub(crate) start_from: Option<usize>,
+    pub(crate) trim: bool,
+    pub(crate) noescape: bool,
+    pub(crate) class: Vec<String>,
+    pub(crate) id: String,
+    pub(crate) file: String,
+}
+
+impl CodeInfo {
+    /// Parse the info string.
+    pub(crate) fn parse(info: &str) -> Result<Self, String> {
+        let mut code = Self::default();
+        let mut rest = info.trim();
+        // Language name
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '[' || c == '{')
+            .unwrap_or(rest.len());
+        code.lang = rest[..end].to_string();
+        rest = rest[end..].trim_start();
+        if let Some(r) = rest.strip_prefix('[') {
+            let (line, r) = r
+                .split_once(']')
+                .ok_or_else(|| "unclosed highlight steps `[`".to_string())?;
+            code.line = line.trim().to_string();
+            rest = r.trim_start();
+        }
+        if let Some(r) = rest.strip_prefix('{') {
+            let (attr, r) = r
+                .split_once('}')
+                .ok_or_else(|| "unclosed attributes `{`".to_string())?;
+            code.attributes(attr)?;
+            rest = r.trim_start();
+        }
+        if rest.is_empty() {
+            Ok(code)
+        } else {
+            Err(format!(
+                "unexpected `{rest}`, the syntax is `lang [steps] {{attributes}}`"
+            ))
+        }
+    }
+
+    fn attributes(&mut self, mut attr: &str) -> Result<(), String> {
+        loop {
+            attr = attr.trim_start();
+            if attr.is_empty() {
+                return Ok(());
+            }
+            let end = attr
+                .find(|c: char| c.is_whitespace() || c == '=')
+                .unwrap_or(attr.len());
+            let (key, r) = attr.split_at(end);
+            attr = r;
+            let value = match attr.strip_prefix('=') {
+                Some(r) if r.starts_with('"') => {
+                    let (value, r) = r[1..]
+                        .split_once('"')
+                        .ok_or_else(|| format!("unclosed quote of `{key}`"))?;
+                    attr = r;
+                    Some(value)
+                }
+                Some(r) => {
+                    let end = r.find(char::is_whitespace).unwrap_or(r.len());
+                    attr = &r[end..];
+                    Some(&r[..end])
                 }
-                head += ">";
+                None => None,
+            };
+            match (key, value) {
+                ("start", Some(n)) => {
+                    let n = n
+                        .parse()
+                        .map_err(|_| format!("invalid starting line number `{n}`"))?;
+                    self.start_from = Some(n);
+                }
+                ("file", Some(file)) => self.file = file.to_string(),
+                ("trim", None) => self.trim = true,
+                ("noescape", None) => self.noescape = true,
+                (key, None) if key.len() > 1 && key.starts_with('.') => {
+                    self.class.push(key[1..].to_string())
+                }
+                (key, None) if key.len() > 1 && key.starts_with('#') => {
+                    self.id = key[1..].to_string()
+                }
+                ("start" | "file", None) => return Err(format!("`{key}` requires a value")),
+                (key, _) => return Err(format!("unknown attribute `{key}`")),
             }
-            Event::Html(head.into())
         }
-        _ => e,
+    }
+
+    /// The attributes of `<code>` tag.
+    fn code_attr(&self) -> String {
+        let mut attr = String::new();
+        if self.start_from.is_some() || !self.line.is_empty() {
+            write!(attr, " data-line-numbers=\"{}\"", html_escape(&self.line)).unwrap();
+        }
+        if let Some(n) = self.start_from.filter(|n| *n != 1) {
+            write!(attr, " data-ln-start-from=\"{n}\"").unwrap();
+        }
+        if self.trim {
+            attr += " data-trim";
+        }
+        attr
+    }
+}
+
+/// A code block, highlighted by the build-time highlighter if enabled.
+///
+/// The highlighted block is skipped by highlight.js, but the line numbers
+/// still work.
+pub(crate) fn code_block(code: &str, info: &CodeInfo, ctx: &Ctx) -> String {
+    let mut s = "<pre".to_string();
+    if !info.id.is_empty() {
+        write!(s, " id=\"{}\"", html_escape(&info.id)).unwrap();
+    }
+    if !info.class.is_empty() {
+        write!(s, " class=\"{}\"", html_escape(&info.class.join(" "))).unwrap();
+    }
+    let lang = html_escape(&info.lang);
+    match &ctx.syntect {
+        Some(syntect) if !info.noescape => write!(
+            s,
+            "><code class=\"language-{lang} nohighlight\" data-noescape style=\"{}\"{}>{}",
+            syntect.style(),
+            info.code_attr(),
+            syntect.highlight(code, &info.lang),
+        )
+        .unwrap(),
+        _ => {
+            s += "><code";
+            if !lang.is_empty() {
+                write!(s, " class=\"language-{lang}\"").unwrap();
+            }
+            s += &info.code_attr();
+            if info.noescape {
+                s += " data-noescape>";
+                s += code;
+            } else {
+                s += ">";
+                escape_html(&mut s, code).unwrap();
+            }
+        }
+    }
+    s += "</code></pre>\n";
+    if info.file.is_empty() {
+        s
+    } else {
+        let file = html_escape(&info.file);
+        format!("<figure class=\"code-block\"><figcaption>{file}</figcaption>{s}</figure>\n")
     }
 }
 
 /// Translate Markdown to HTML.
-pub fn md2html(text: &str) -> String {
+///
+/// The fenced code blocks of `dot` language are laid out as SVG. The math is
+/// translated by the build-time renderer if enabled. The extensions are
+/// decided by [`MarkdownOption`].
+pub fn md2html(text: &str, ctx: &Ctx) -> String {
+    if ctx.math.renderer == MathRenderer::MathML && !ctx.markdown.math {
+        let (text, maths) = extract_math(text);
+        let maths = maths
+            .into_iter()
+            .map(|(tex, display)| ctx.math.render(&tex, display, ctx))
+            .collect::<Vec<_>>();
+        return restore_math(&md_events(&text, ctx), &maths);
+    }
+    md_events(text, ctx)
+}
+
+fn md_events(text: &str, ctx: &Ctx) -> String {
+    let mut events = Vec::new();
+    let mut block = None;
+    for e in Parser::new_ext(text, ctx.markdown.options()) {
+        match (e, &mut block) {
+            (Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))), None) => {
+                block = Some((info.to_string(), String::new()))
+            }
+            (Event::Text(text), Some((_, code))) => *code += &text,
+            (Event::End(TagEnd::CodeBlock), Some(_)) => {
+                let (info, code) = block.take().unwrap();
+                let html = if info.trim() == "dot" {
+                    dot2svg(&code, &None, &None, ctx)
+                        .map_err(|e| ctx.error(e))
+                        .unwrap_or_default()
+                } else {
+                    match CodeInfo::parse(&info) {
+                        Ok(info) => code_block(&code, &info, ctx),
+                        Err(e) => {
+                            ctx.error(format!("invalid code block info `{info}`: {e}"));
+                            String::new()
+                        }
+                    }
+                };
+                events.push(Event::Html(html.into()));
+            }
+            (Event::Html(_) | Event::InlineHtml(_), _) if ctx.markdown.strip_html => {}
+            (Event::InlineMath(tex), _) => events.push(math_event(&tex, false, ctx)),
+            (Event::DisplayMath(tex), _) => events.push(math_event(&tex, true, ctx)),
+            (Event::Text(text), None) => match events.last_mut() {
+                Some(Event::Text(last)) => *last = format!("{last}{text}").into(),
+                _ => events.push(Event::Text(text)),
+            },
+            (e, _) => events.push(e),
+        }
+    }
+    // Merged text events may contain the references and the citations, except
+    // the indented code blocks
+    let mut in_code = false;
+    for e in &mut events {
+        match e {
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::Text(text) if !in_code && (text.contains('@') || text.contains("[[")) => {
+                let text = mark_refs(text);
+                *e = match &ctx.bib {
+                    Some(bib) if text.contains("[@") => {
+                        Event::InlineHtml(bib.replace(&text, ctx).into())
+                    }
+                    _ => Event::Text(text.into()),
+                };
+            }
+            _ => {}
+        }
+    }
     let mut doc = String::new();
-    push_html(&mut doc, Parser::new_ext(text, MARKED).map(marked));
+    push_html(&mut doc, events.into_iter());
     doc
 }
+
+/// The build-time math is HTML, otherwise it is the text with brackets.
+fn math_event(tex: &str, display: bool, ctx: &Ctx) -> Event<'static> {
+    let math = ctx.math.render(tex, display, ctx);
+    if ctx.math.renderer == MathRenderer::MathML {
+        Event::Html(math.into())
+    } else {
+        Event::Text(math.into())
+    }
+}
@@ -1,11 +1,14 @@
 use super::*;
 
 /// Embed images.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct Img {
-    /// Image `<caption>`.
+    /// Image `<caption>`. The captioned images are numbered as "Figure n".
     pub label: String,
+    /// Cross-reference label starts with `fig:`, such as `fig:arch`, then
+    /// refer it as `@fig:arch` in Markdown.
+    pub id: String,
     /// Pop-up modal image, boolean `false` by default.
     pub pop: bool,
     /// This item is sized. (*flatten*)
@@ -14,7 +17,7 @@ pub struct Img {
 }
 
 /// Embed videos.
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct Video {
     /// Allow controls, boolean `true` by default.
@@ -43,7 +46,7 @@ impl Default for Video {
 ///
 /// Please be aware that `<iframe>` maybe slow down your web browser and cause
 /// security issues!
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct IFrame {
     /// This item is sized. (*flatten*)
@@ -52,8 +55,8 @@ pub struct IFrame {
 }
 
 impl ToHtml for Img {
-    fn to_html(self, _ctx: &Ctx) -> String {
-        let Self { label, pop, size } = self;
+    fn to_html(self, ctx: &Ctx) -> String {
+        let Self { label, id, pop, size } = self;
         let pop = if pop {
             " class=\"img-pop\" onclick=\"show_modal(this)\" title=\"click to pop-up the image\""
         } else {
@@ -61,9 +64,15 @@ impl ToHtml for Img {
         };
         let s = format!("<img alt=\"{label}\"{pop}{size}/>");
         if label.is_empty() {
+            if !id.is_empty() {
+                ctx.error(format!("label `{id}` requires an image caption"));
+            }
             s
         } else {
-            format!("<figure>{s}<figcaption>{label}</figcaption></figure>")
+            let no = Ctx::count(&ctx.fig);
+            ctx.label(&id, "fig:", &no.to_string());
+            let id = html_escape(&id).wrap(" id=\"", "\"");
+            format!("<figure{id}>{s}<figcaption>Figure {no}: {label}</figcaption></figure>")
         }
     }
 }
@@ -2,14 +2,16 @@ use super::*;
 use yaml_peg::serde::Stringify;
 
 /// Sized item option.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct Sized {
     /// Source link.
     pub src: String,
     /// Item width.
+    #[schemars(with = "schema::Stringify")]
     pub width: Stringify,
     /// Item height.
+    #[schemars(with = "schema::Stringify")]
     pub height: Stringify,
 }
 
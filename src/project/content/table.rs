@@ -0,0 +1,210 @@
+use super::*;
+use yaml_peg::serde::Stringify;
+
+/// Render a CSV / TSV file as a table.
+///
+/// ```yaml
+/// table:
+///   src: data/bench.csv
+///   cols: [name, time]
+///   align: [left, right]
+///   precision: [~, 2]
+///   highlight: min
+///   row-frag: fade-up
+/// ```
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct Table {
+    /// File path. The `.tsv` files are separated by tab, otherwise comma.
+    pub src: String,
+    /// Table caption. The captioned tables are numbered as "Table n".
+    pub caption: String,
+    /// Cross-reference label starts with `tab:`, such as `tab:bench`, then
+    /// refer it as `@tab:bench` in Markdown.
+    pub id: String,
+    /// Number of header rows, defaults to 1.
+    pub header: usize,
+    /// Selected columns by header names or indexes (from zero), defaults to
+    /// all columns.
+    #[schemars(with = "Vec<schema::Stringify>")]
+    pub cols: Vec<Stringify>,
+    /// Alignments (`left` / `center` / `right`) of the selected columns.
+    pub align: Vec<String>,
+    /// Digits after the decimal point of the selected columns, `~` to keep the
+    /// original text.
+    pub precision: Vec<Option<usize>>,
+    /// Highlight the maximum (`max`) or minimum (`min`) number of each column.
+    pub highlight: String,
+    /// Reveal the body rows one by one, the value is the fragment style.
+    #[serde(rename = "row-frag")]
+    pub row_frag: Option<String>,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self {
+            src: String::new(),
+            caption: String::new(),
+            id: String::new(),
+            header: 1,
+            cols: Vec::new(),
+            align: Vec::new(),
+            precision: Vec::new(),
+            highlight: String::new(),
+            row_frag: None,
+        }
+    }
+}
+
+impl ToHtml for Table {
+    fn to_html(self, ctx: &Ctx) -> String {
+        let Self {
+            src,
+            caption,
+            id,
+            header,
+            cols,
+            align,
+            precision,
+            highlight,
+            row_frag,
+        } = self;
+        if let Some(a) = align
+            .iter()
+            .find(|a| !matches!(a.as_str(), "left" | "center" | "right"))
+        {
+            ctx.error(format!(
+                "invalid alignment `{a}`, expected `left`, `center` or `right`"
+            ));
+            return String::new();
+        }
+        if !matches!(highlight.as_str(), "" | "max" | "min") {
+            ctx.error(format!(
+                "invalid highlight `{highlight}`, expected `max` or `min`"
+            ));
+            return String::new();
+        }
+        let doc = match ctx.read(&src) {
+            Some(doc) => doc,
+            None => return String::new(),
+        };
+        let delimiter = if src.ends_with(".tsv") { b'\t' } else { b',' };
+        let rows = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(doc.as_bytes())
+            .records()
+            .map(|r| r.map(|r| r.iter().map(str::to_string).collect::<Vec<_>>()))
+            .collect::<Result<Vec<_>, _>>();
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(e) => {
+                ctx.error(format!("{src}: {e}"));
+                return String::new();
+            }
+        };
+        // Column selection
+        let index = if cols.is_empty() {
+            (0..rows.iter().map(Vec::len).max().unwrap_or_default()).collect()
+        } else {
+            let mut index = Vec::with_capacity(cols.len());
+            for col in &cols {
+                let i = match col {
+                    Stringify::Int(i) => usize::try_from(*i).ok(),
+                    col => {
+                        let name = col.to_string();
+                        rows.iter()
+                            .take(header)
+                            .find_map(|r| r.iter().position(|s| s.trim() == name))
+                    }
+                };
+                match i {
+                    Some(i) => index.push(i),
+                    None => {
+                        ctx.error(format!("{src}: column `{col}` not found"));
+                        return String::new();
+                    }
+                }
+            }
+            index
+        };
+        let rows = rows
+            .into_iter()
+            .map(|r| {
+                index
+                    .iter()
+                    .enumerate()
+                    .map(|(j, i)| {
+                        let cell = r.get(*i).map(|s| s.trim()).unwrap_or_default();
+                        match (precision.get(j).copied().flatten(), cell.parse::<f64>()) {
+                            (Some(p), Ok(n)) => format!("{n:.p$}"),
+                            _ => cell.to_string(),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let header = header.min(rows.len());
+        let (head, body) = rows.split_at(header);
+        // Highlighted cells
+        let best = (0..index.len())
+            .map(|j| {
+                let nums = body.iter().filter_map(|r| r[j].parse::<f64>().ok());
+                match highlight.as_str() {
+                    "max" => nums.reduce(f64::max),
+                    "min" => nums.reduce(f64::min),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        let style = |j: usize| {
+            align
+                .get(j)
+                .map(|a| format!(" style=\"text-align: {a}\""))
+                .unwrap_or_default()
+        };
+        let mut s = format!("<table{}>\n", html_escape(&id).wrap(" id=\"", "\""));
+        if caption.is_empty() {
+            if !id.is_empty() {
+                ctx.error(format!("label `{id}` requires a table caption"));
+            }
+        } else {
+            let no = Ctx::count(&ctx.tab);
+            ctx.label(&id, "tab:", &no.to_string());
+            s += &format!("<caption>Table {no}: {}</caption>\n", html_escape(&caption));
+        }
+        if !head.is_empty() {
+            s += "<thead>\n";
+            for r in head {
+                s += "<tr>";
+                for (j, cell) in r.iter().enumerate() {
+                    s += &format!("<th{}>{}</th>", style(j), html_escape(cell));
+                }
+                s += "</tr>\n";
+            }
+            s += "</thead>\n";
+        }
+        s += "<tbody>\n";
+        for r in body {
+            match &row_frag {
+                Some(frag) => {
+                    let i = ctx.frag.get();
+                    let frag = html_escape(frag);
+                    s += &format!("<tr class=\"fragment {frag}\" data-fragment-index=\"{i}\">");
+                    ctx.frag.set(i + 1);
+                }
+                None => s += "<tr>",
+            }
+            for (j, cell) in r.iter().enumerate() {
+                let hl = match (best[j], cell.parse::<f64>()) {
+                    (Some(best), Ok(n)) if n == best => " class=\"table-hl\"",
+                    _ => "",
+                };
+                s += &format!("<td{hl}{}>{}</td>", style(j), html_escape(cell));
+            }
+            s += "</tr>\n";
+        }
+        s + "</tbody>\n</table>\n"
+    }
+}
@@ -0,0 +1,167 @@
+use super::*;
+use std::cell::Cell;
+
+const OPEN: char = '\u{e002}';
+const CLOSE: char = '\u{e003}';
+/// The label prefixes and the reference names.
+const PREFIXES: &[(&str, &str)] = &[("fig:", "Figure"), ("tab:", "Table"), ("eq:", "Eq.")];
+
+fn is_label_char(c: char) -> bool {
+    c.is_alphanumeric() || "_-.:".contains(c)
+}
+
+/// Split the inner text of `[[slide-id|text]]` into the slide id and the
+/// text. The id should be made of the slug characters and not be a number,
+/// otherwise it is not a link.
+pub(crate) fn slide_link(link: &str) -> Option<(&str, &str)> {
+    let (id, text) = link.split_once('|').unwrap_or((link, ""));
+    let id = id.trim();
+    let slug = id.chars().all(|c| c.is_alphanumeric() || "_-".contains(c));
+    let numeric = id.chars().all(|c| c.is_ascii_digit());
+    (!id.is_empty() && slug && !numeric).then_some((id, text))
+}
+
+/// Check the label prefix of the kind, return the reference text.
+fn ref_text(label: &str, prefix: &str, no: &str) -> Result<String, String> {
+    match PREFIXES.iter().find(|(p, _)| *p == prefix) {
+        Some((_, name)) if label.len() > prefix.len() && label.starts_with(prefix) => {
+            Ok(if prefix == "eq:" {
+                format!("{name} ({no})")
+            } else {
+                format!("{name} {no}")
+            })
+        }
+        _ => Err(format!("label `{label}` should start with `{prefix}`")),
+    }
+}
+
+impl Ctx {
+    /// Increase the counter and return the number.
+    pub(crate) fn count(counter: &Cell<usize>) -> usize {
+        counter.set(counter.get() + 1);
+        counter.get()
+    }
+
+    /// Register a cross-reference label to the current slide. The empty label
+    /// is skipped.
+    pub(crate) fn label(&self, label: &str, prefix: &str, no: &str) {
+        if label.is_empty() {
+            return;
+        }
+        if !label.chars().all(is_label_char) {
+            return self.error(format!(
+                "label `{label}` should only contain the letters, digits, `_`, `-`, `.` and `:`"
+            ));
+        }
+        let text = match ref_text(label, prefix, no) {
+            Ok(text) => text,
+            Err(e) => return self.error(e),
+        };
+        let anchor = self.anchor.borrow().clone();
+        if self
+            .refs
+            .borrow_mut()
+            .insert(label.to_string(), (text, anchor))
+            .is_some()
+        {
+            self.error(format!("duplicated label `{label}`"));
+        }
+    }
+}
+
+/// Mark the `[[slide-id]]` / `[[slide-id|text]]` links and the `@fig:name` /
+/// `@tab:name` / `@eq:name` references in the plain text, they are resolved
+/// by [`resolve_refs`] after all slides are rendered. The brackets without a
+/// slide id are left as text.
+pub(crate) fn mark_refs(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find("[[") {
+        let Some(j) = rest[i..].find("]]") else {
+            break;
+        };
+        let link = &rest[i + 2..i + j];
+        if slide_link(link).is_none() {
+            mark_labels(&mut s, &rest[..i + 2]);
+            rest = &rest[i + 2..];
+            continue;
+        }
+        mark_labels(&mut s, &rest[..i]);
+        s.push(OPEN);
+        s.push('#');
+        s += link.trim();
+        s.push(CLOSE);
+        rest = &rest[i + j + 2..];
+    }
+    mark_labels(&mut s, rest);
+    s
+}
+
+fn mark_labels(s: &mut String, mut rest: &str) {
+    while let Some(i) = rest.find('@') {
+        let label = &rest[i + 1..];
+        let end = label.find(|c| !is_label_char(c)).unwrap_or(label.len());
+        // The trailing dots and colons belong to the sentence
+        let label = label[..end].trim_end_matches(['.', ':']);
+        let after_word = rest[..i].chars().next_back().is_some_and(is_label_char);
+        let valid = PREFIXES
+            .iter()
+            .any(|(p, _)| label.len() > p.len() && label.starts_with(p));
+        if valid && !after_word {
+            *s += &rest[..i];
+            s.push(OPEN);
+            *s += label;
+            s.push(CLOSE);
+            rest = &rest[i + 1 + label.len()..];
+        } else {
+            *s += &rest[..=i];
+            rest = &rest[i + 1..];
+        }
+    }
+    *s += rest;
+}
+
+/// Replace the marked references with the links, the undefined labels are
+/// errors.
+pub(crate) fn resolve_refs(html: &str, ctx: &Ctx) -> String {
+    let refs = ctx.refs.borrow();
+    let mut s = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(i) = rest.find(OPEN) {
+        s += &rest[..i];
+        let tail = &rest[i + OPEN.len_utf8()..];
+        let Some(j) = tail.find(CLOSE) else {
+            rest = tail;
+            break;
+        };
+        let label = &tail[..j];
+        if let Some(link) = label.strip_prefix('#') {
+            let (id, text) = slide_link(link).unwrap_or((link, ""));
+            match ctx.slides.borrow().get(id) {
+                Some(title) => {
+                    let text = if text.trim().is_empty() {
+                        html_escape(title)
+                    } else {
+                        text.trim().to_string()
+                    };
+                    s += &format!("<a href=\"#/{id}\">{text}</a>");
+                }
+                None => {
+                    ctx.error(format!("undefined slide link `[[{id}]]`"));
+                    s += &format!("[[{link}]]");
+                }
+            }
+        } else {
+            match refs.get(label) {
+                Some((text, anchor)) => s += &format!("<a href=\"#/{anchor}\">{text}</a>"),
+                None => {
+                    ctx.error(format!("undefined reference `@{label}`"));
+                    s += "@";
+                    s += label;
+                }
+            }
+        }
+        rest = &tail[j + CLOSE.len_utf8()..];
+    }
+    s + rest
+}
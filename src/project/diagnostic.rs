@@ -0,0 +1,298 @@
+use super::*;
+use pulldown_cmark_escape::escape_html;
+use serde::de::DeserializeOwned;
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::IsTerminal as _,
+};
+use yaml_peg::Yaml;
+
+const STACKS: &[&str] = &[
+    "blocks",
+    "h-stack",
+    "v-stack",
+    "h-stack-border",
+    "v-stack-border",
+];
+const STYLE: &str = "\
+#diagnostics { position: fixed; inset: 0; z-index: 1000; overflow: auto; padding: 2em;
+    background: rgba(24, 24, 27, 0.92); color: #e4e4e7; font: 16px/1.5 sans-serif; text-align: left; }
+#diagnostics h2 { margin: 0 0 1em; color: #f87171; font-size: 1.4em; }
+#diagnostics ul { margin: 0; padding: 0; list-style: none; }
+#diagnostics li { margin-bottom: 1.5em; padding-left: 1em; border-left: 4px solid #f87171; }
+#diagnostics li.warning { border-color: #facc15; }
+#diagnostics p { margin: 0.2em 0; }
+#diagnostics .msg { font-weight: bold; }
+#diagnostics .loc { color: #93c5fd; font-family: monospace; }
+#diagnostics pre { margin: 0.4em 0; padding: 0.5em; background: #000; color: #e4e4e7; }
+#diagnostics .hint { color: #67e8f9; }";
+
+/// An error or a warning with its source location.
+///
+/// The errors are collected during the loading, so all of them can be shown
+/// at once.
+#[derive(Clone, Default)]
+pub struct Diagnostic {
+    /// Error message.
+    pub msg: String,
+    /// The suggestion to fix the error, can be empty.
+    pub hint: String,
+    /// The global source position, resolved into the location by the loader.
+    pub pos: Option<u64>,
+    /// Source file, empty if the location is unknown.
+    pub file: String,
+    /// Line number, starts from 1.
+    pub line: usize,
+    /// Column number, starts from 1.
+    pub column: usize,
+    /// The source line of the location.
+    pub excerpt: String,
+    /// A warning, which does not stop the loading.
+    pub warning: bool,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic without the location.
+    pub fn new(msg: impl ToString) -> Self {
+        Self { msg: msg.to_string(), ..Self::default() }
+    }
+
+    /// Create a diagnostic at the global source position.
+    pub fn at(msg: impl ToString, pos: u64) -> Self {
+        Self { pos: Some(pos), ..Self::new(msg) }
+    }
+
+    /// Explain the deserialization error of the field at the node.
+    ///
+    /// The untagged enums only report "did not match any variant", so the
+    /// expected forms are given by the hints.
+    fn field(SerdeError { msg, pos }: SerdeError, field: &str, n: &NodeRc) -> Self {
+        // The buffered (flatten and untagged) fields lose their positions
+        let pos = pos.max(n.pos());
+        let Some(name) = msg.strip_prefix("data did not match any variant of untagged enum ")
+        else {
+            return Self::at(msg, pos).hint(field.wrap("in the `", "` field"));
+        };
+        let hint = match (name, field) {
+            ("Optional", "background") => {
+                "expected `false`, a color, or a map with `src`, `size`, `position`, \
+                 `repeat` and `opacity`"
+            }
+            ("Background", _) => {
+                "expected a color, such as `#000000` or `rgb(0, 0, 0)`, or a map with \
+                 `src`, `size`, `position`, `repeat` and `opacity`"
+            }
+            ("Optional", "outline") | ("Outline", _) => {
+                "expected a boolean, a title, or a map with `title`, `depth`, \
+                 `numbering`, `columns`, `place` and `dividers`"
+            }
+            ("Optional", _) => "expected a boolean or a value",
+            ("JsType", _) => {
+                "expected a boolean, a number, a string, a sequence or a map, \
+                 the null value is not allowed"
+            }
+            ("Author", _) => "expected a name, or a map with `name`, `affiliation` and `email`",
+            ("InlineList", _) => "expected a map of the item, or a list of the maps",
+            _ => "",
+        };
+        let msg = if field.is_empty() {
+            "invalid value".to_string()
+        } else {
+            format!("invalid value of `{field}`")
+        };
+        Self::at(msg, pos).hint(hint)
+    }
+
+    pub(crate) fn hint(self, hint: impl ToString) -> Self {
+        Self { hint: hint.to_string(), ..self }
+    }
+
+    fn render(&self, color: bool) -> String {
+        let paint = |code: &str, s: &str| {
+            if color {
+                format!("\x1b[{code}m{s}\x1b[0m")
+            } else {
+                s.to_string()
+            }
+        };
+        let (level, code) = if self.warning {
+            ("warning", "1;33")
+        } else {
+            ("error", "1;31")
+        };
+        let mut s = format!(
+            "{}{}\n",
+            paint(code, level),
+            paint("1", &format!(": {}", self.msg))
+        );
+        let mut gutter = String::new();
+        if !self.file.is_empty() {
+            let no = self.line.to_string();
+            gutter = " ".repeat(no.len() + 1);
+            let caret = " ".repeat(self.column.saturating_sub(1)) + "^";
+            s += &format!(
+                "{}{} {}:{}:{}\n{gutter}{}\n{} {} {}\n{gutter}{} {}\n",
+                &gutter[1..],
+                paint("1;34", "-->"),
+                self.file,
+                self.line,
+                self.column,
+                paint("1;34", "|"),
+                paint("1;34", &no),
+                paint("1;34", "|"),
+                self.excerpt,
+                paint("1;34", "|"),
+                paint(code, &caret),
+            );
+        }
+        if !self.hint.is_empty() {
+            s += &format!("{gutter}{} {}\n", paint("1;36", "= hint:"), self.hint);
+        }
+        s
+    }
+
+    fn to_html(&self) -> String {
+        let escape = |s: &str| {
+            let mut out = String::new();
+            escape_html(&mut out, s).unwrap();
+            out
+        };
+        let level = if self.warning { "warning" } else { "error" };
+        let mut s = format!(
+            "<li class=\"{level}\">\n<p class=\"msg\">{level}: {}</p>\n",
+            escape(&self.msg)
+        );
+        if !self.file.is_empty() {
+            s += &format!(
+                "<p class=\"loc\">{}:{}:{}</p>\n<pre>{}\n{}^</pre>\n",
+                escape(&self.file),
+                self.line,
+                self.column,
+                escape(&self.excerpt),
+                " ".repeat(self.column.saturating_sub(1)),
+            );
+        }
+        s + &escape(&self.hint).wrap("<p class=\"hint\">hint: ", "</p>\n") + "</li>\n"
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(&self.render(false))
+    }
+}
+
+/// All of the diagnostics of a project.
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl Diagnostics {
+    /// Number of the errors, excluding the warnings.
+    pub fn errors(&self) -> usize {
+        self.0.iter().filter(|d| !d.warning).count()
+    }
+
+    /// Print the diagnostics to stderr, colored if it is a terminal.
+    pub fn print(&self) {
+        let color = std::io::stderr().is_terminal();
+        for d in &self.0 {
+            eprintln!("{}", d.render(color));
+        }
+    }
+
+    /// The diagnostics in JSON, a list of the objects with the `level`,
+    /// `message`, `hint`, `file`, `line` and `column` fields.
+    pub fn to_json(&self) -> String {
+        let list = self
+            .0
+            .iter()
+            .map(|d| {
+                serde_json::json!({
+                    "level": if d.warning { "warning" } else { "error" },
+                    "message": d.msg,
+                    "hint": d.hint,
+                    "file": d.file,
+                    "line": d.line,
+                    "column": d.column,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_string_pretty(&list).unwrap()
+    }
+
+    /// The error overlay of the diagnostics, a standalone HTML element.
+    pub fn to_html(&self) -> String {
+        let n = self.errors();
+        let s = if n > 1 { "s" } else { "" };
+        let items = self.0.iter().map(Diagnostic::to_html).collect::<String>();
+        format!(
+            "<div id=\"diagnostics\">\n<style>\n{STYLE}\n</style>\n\
+             <h2>{n} error{s}</h2>\n<ul>\n{items}</ul>\n</div>\n"
+        )
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for d in &self.0 {
+            writeln!(f, "{d}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Diagnostic> for Diagnostics {
+    fn from(d: Diagnostic) -> Self {
+        Self(vec![d])
+    }
+}
+
+impl From<IoError> for Diagnostics {
+    fn from(e: IoError) -> Self {
+        Diagnostic::new(e).into()
+    }
+}
+
+impl From<Diagnostics> for IoError {
+    fn from(d: Diagnostics) -> Self {
+        IoError::new(ErrorKind::InvalidData, d.to_string().trim_end().to_string())
+    }
+}
+
+/// Deserialize a map node, or find out all of its invalid fields.
+///
+/// Each field is deserialized alone, and the slides and the content blocks
+/// in the nested lists are checked item by item.
+pub(crate) fn check<T>(n: &NodeRc, diags: &mut Vec<Diagnostic>) -> Option<T>
+where
+    T: DeserializeOwned,
+{
+    let e = match T::deserialize(n.clone()) {
+        Ok(t) => return Some(t),
+        Err(e) => e,
+    };
+    let len = diags.len();
+    for (k, v) in n.as_map().unwrap_or_default() {
+        let field = NodeRc::new(Yaml::from_iter([(k.clone(), v.clone())]), n.pos(), "");
+        let Err(e) = T::deserialize(field) else {
+            continue;
+        };
+        let key = k.as_str().unwrap_or_default();
+        let len = diags.len();
+        match v.as_seq() {
+            Ok(seq) if key == "sub" => seq.iter().for_each(|n| {
+                check::<Slide>(n, diags);
+            }),
+            Ok(seq) if STACKS.contains(&key) => seq.iter().for_each(|n| {
+                check::<Content>(n, diags);
+            }),
+            _ => (),
+        }
+        if diags.len() == len {
+            diags.push(Diagnostic::field(e, key, &v));
+        }
+    }
+    if diags.len() == len {
+        diags.push(Diagnostic::field(e, "", n));
+    }
+    None
+}
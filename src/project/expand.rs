@@ -0,0 +1,388 @@
+use super::{source::Sources, Diagnostic, Diagnostics};
+use std::{collections::HashMap, ops::Range};
+use yaml_peg::{repr::RcRepr, Map, NodeRc, Yaml};
+
+const MAX_DEPTH: usize = 32;
+
+fn error(msg: String, n: &NodeRc) -> Diagnostics {
+    Diagnostic::at(msg, n.pos()).into()
+}
+
+/// The byte ranges of the fenced code blocks and the inline code spans in the
+/// Markdown text.
+fn code_ranges(s: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut fence = None::<(usize, &str)>;
+    let mut i = 0;
+    for line in s.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = |c| &trimmed[..trimmed.len() - trimmed.trim_start_matches(c).len()];
+        match fence {
+            Some((start, open)) => {
+                if marker(open.as_bytes()[0] as char).len() >= open.len() {
+                    ranges.push(start..i + line.len());
+                    fence = None;
+                }
+            }
+            None => {
+                let open = [marker('`'), marker('~')]
+                    .into_iter()
+                    .find(|m| m.len() >= 3);
+                match open {
+                    Some(open) => fence = Some((i, open)),
+                    None => inline_code(line, i, &mut ranges),
+                }
+            }
+        }
+        i += line.len();
+    }
+    if let Some((start, _)) = fence {
+        ranges.push(start..s.len());
+    }
+    ranges
+}
+
+/// The inline code spans, a backtick run closed by the run of the same length.
+fn inline_code(line: &str, offset: usize, ranges: &mut Vec<Range<usize>>) {
+    let run = |s: &str| s.len() - s.trim_start_matches('`').len();
+    let mut i = 0;
+    while let Some(j) = line[i..].find('`') {
+        let start = i + j;
+        let n = run(&line[start..]);
+        let mut k = start + n;
+        let end = loop {
+            match line[k..].find('`') {
+                Some(j) => {
+                    let m = run(&line[k + j..]);
+                    if m == n {
+                        break Some(k + j + m);
+                    }
+                    k += j + m;
+                }
+                None => break None,
+            }
+        };
+        match end {
+            Some(end) => {
+                ranges.push(offset + start..offset + end);
+                i = end;
+            }
+            None => i = start + n,
+        }
+    }
+}
+
+/// Slide template expander.
+///
+/// Please see [`Metadata::templates`](super::Metadata::templates) for the
+/// syntax.
+#[derive(Default)]
+pub(crate) struct Expander {
+    templates: HashMap<String, NodeRc>,
+}
+
+impl Expander {
+    /// Collect templates from the metadata node.
+    pub(crate) fn new(metadata: Option<&NodeRc>) -> Result<Self, Diagnostics> {
+        let mut templates = HashMap::new();
+        let map = match metadata.and_then(|n| n.get("templates").ok()) {
+            Some(n) => n
+                .as_map()
+                .map_err(|pos| Diagnostic::at("templates must be a map", pos))?,
+            None => return Ok(Self::default()),
+        };
+        for (k, v) in map {
+            let name = k
+                .as_str()
+                .map_err(|pos| Diagnostic::at("template name must be a string", pos))?;
+            templates.insert(name.to_string(), v);
+        }
+        Ok(Self { templates })
+    }
+
+    /// Expand all `use` nodes.
+    pub(crate) fn expand(&self, n: &NodeRc, src: &Sources) -> Result<NodeRc, Diagnostics> {
+        self.expand_depth(n, 0, src)
+    }
+
+    fn expand_depth(&self, n: &NodeRc, depth: usize, src: &Sources) -> Result<NodeRc, Diagnostics> {
+        if depth > MAX_DEPTH {
+            return Err(error("templates are nested too deeply".to_string(), n));
+        }
+        let yaml: Yaml<RcRepr> = match n.yaml() {
+            Yaml::Seq(s) => s
+                .iter()
+                .map(|n| self.expand_depth(n, depth, src))
+                .collect::<Result<_, _>>()?,
+            Yaml::Map(m) => match n.get("use") {
+                Ok(name) => return self.instance(n, m, name, depth, src),
+                Err(_) => m
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), self.expand_depth(v, depth, src)?)))
+                    .collect::<Result<_, Diagnostics>>()?,
+            },
+            _ => return Ok(n.clone()),
+        };
+        Ok(NodeRc::new(yaml, n.pos(), n.tag()))
+    }
+
+    fn instance(
+        &self,
+        n: &NodeRc,
+        m: &Map<RcRepr>,
+        name: &NodeRc,
+        depth: usize,
+        src: &Sources,
+    ) -> Result<NodeRc, Diagnostics> {
+        let key = name
+            .as_str()
+            .map_err(|_| error("template name must be a string".to_string(), name))?;
+        let template = self
+            .templates
+            .get(key)
+            .ok_or_else(|| error(format!("undefined template `{key}`"), name))?;
+        let args = match n.get("with") {
+            Ok(args) => args
+                .as_map()
+                .map_err(|_| error("template arguments must be a map".to_string(), args))?,
+            Err(_) => Default::default(),
+        };
+        let args = args
+            .into_iter()
+            .map(|(k, v)| match k.as_str() {
+                Ok(k) => Ok((k.to_string(), v)),
+                Err(_) => Err(error("argument name must be a string".to_string(), &k)),
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let body = substitute(template, key, &args, name, src)?;
+        let body = self.expand_depth(&body, depth + 1, src)?;
+        // Local keys override the template
+        let local = m
+            .iter()
+            .filter(|(k, _)| !matches!(k.as_str(), Ok("use" | "with")))
+            .map(|(k, v)| Ok((k.clone(), self.expand_depth(v, depth, src)?)))
+            .collect::<Result<Vec<_>, Diagnostics>>()?;
+        let yaml: Yaml<RcRepr> = match body.yaml() {
+            Yaml::Map(body) => body.clone().into_iter().chain(local).collect(),
+            _ if local.is_empty() => return Ok(body),
+            _ => {
+                let msg = format!("template `{key}` is not a map, cannot add other keys");
+                return Err(error(msg, name));
+            }
+        };
+        Ok(NodeRc::new(yaml, n.pos(), n.tag()))
+    }
+}
+
+/// Replace the `${name}` placeholders, the missing values are reported at
+/// the `use` node.
+fn substitute(
+    n: &NodeRc,
+    template: &str,
+    args: &HashMap<String, NodeRc>,
+    site: &NodeRc,
+    src: &Sources,
+) -> Result<NodeRc, Diagnostics> {
+    let get = |name: &str| {
+        args.get(name).ok_or_else(|| {
+            let msg = format!("template `{template}` requires a value for `{name}`");
+            let d = src.locate(Diagnostic::at("", n.pos()));
+            let hint = format!("the placeholder is at {}:{}:{}", d.file, d.line, d.column);
+            Diagnostics::from(Diagnostic::at(msg, site.pos()).hint(hint))
+        })
+    };
+    let yaml: Yaml<RcRepr> = match n.yaml() {
+        Yaml::Str(s) => {
+            if let Some(name) = s.strip_prefix("${").and_then(|s| s.strip_suffix('}')) {
+                if is_ident(name) {
+                    // The whole string is replaced by the node
+                    return get(name).cloned();
+                }
+            }
+            let mut text = String::with_capacity(s.len());
+            let mut rest = s.as_str();
+            while let Some(i) = rest.find('$') {
+                text += &rest[..i];
+                rest = &rest[i..];
+                if let Some(r) = rest.strip_prefix("$${") {
+                    text += "${";
+                    rest = r;
+                } else if let Some((name, r)) = rest
+                    .strip_prefix("${")
+                    .and_then(|r| r.split_once('}'))
+                    .filter(|(name, _)| is_ident(name))
+                {
+                    let arg = get(name)?;
+                    match arg.yaml() {
+                        Yaml::Str(s) | Yaml::Int(s) | Yaml::Float(s) => text += s,
+                        Yaml::Bool(b) => text += &b.to_string(),
+                        Yaml::Null => (),
+                        _ => {
+                            let msg = format!("`{name}` must be a scalar in a string");
+                            return Err(error(msg, arg));
+                        }
+                    }
+                    rest = r;
+                } else {
+                    text.push('$');
+                    rest = &rest[1..];
+                }
+            }
+            text += rest;
+            Yaml::Str(text)
+        }
+        Yaml::Seq(s) => s
+            .iter()
+            .map(|n| substitute(n, template, args, site, src))
+            .collect::<Result<_, _>>()?,
+        Yaml::Map(m) => m
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), substitute(v, template, args, site, src)?)))
+            .collect::<Result<_, Diagnostics>>()?,
+        _ => return Ok(n.clone()),
+    };
+    Ok(NodeRc::new(yaml, n.pos(), n.tag()))
+}
+
+fn is_ident(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Project variable interpolator.
+///
+/// Please see [`Metadata::vars`](super::Metadata::vars) for the syntax.
+#[derive(Default)]
+pub(crate) struct Vars {
+    vars: HashMap<String, String>,
+}
+
+impl Vars {
+    /// The fields that accept variables.
+    const FIELDS: &'static [&'static str] = &[
+        "title",
+        "title-hidden",
+        "title-only",
+        "doc",
+        "note",
+        "fit",
+        "label",
+        "src",
+    ];
+
+    /// Collect variables from the metadata node.
+    pub(crate) fn new(metadata: Option<&NodeRc>) -> Result<Self, Diagnostics> {
+        let map = match metadata.and_then(|n| n.get("vars").ok()) {
+            Some(n) => n
+                .as_map()
+                .map_err(|_| error("vars must be a map".to_string(), n))?,
+            None => return Ok(Self::default()),
+        };
+        let mut vars = HashMap::new();
+        for (k, v) in map {
+            let name = k
+                .as_str()
+                .map_err(|_| error("variable name must be a string".to_string(), &k))?;
+            let value = match v.yaml() {
+                Yaml::Str(s) | Yaml::Int(s) | Yaml::Float(s) => s.clone(),
+                Yaml::Bool(b) => b.to_string(),
+                Yaml::Null => String::new(),
+                _ => return Err(error(format!("variable `{name}` must be a scalar"), &v)),
+            };
+            vars.insert(name.to_string(), value);
+        }
+        Ok(Self { vars })
+    }
+
+    /// Interpolate the title and the footer of the metadata.
+    pub(crate) fn metadata(&self, n: &NodeRc, src: &Sources) -> Result<NodeRc, Diagnostics> {
+        let m = match n.yaml() {
+            Yaml::Map(m) => m,
+            _ => return Ok(n.clone()),
+        };
+        let yaml: Yaml<RcRepr> = m
+            .iter()
+            .map(|(k, v)| match k.as_str() {
+                Ok("title") => Ok((k.clone(), self.field(v, src)?)),
+                Ok("footer") => Ok((k.clone(), self.interpolate(v, src)?)),
+                _ => Ok((k.clone(), v.clone())),
+            })
+            .collect::<Result<_, Diagnostics>>()?;
+        Ok(NodeRc::new(yaml, n.pos(), n.tag()))
+    }
+
+    /// Interpolate the user fields of the slides.
+    pub(crate) fn interpolate(&self, n: &NodeRc, src: &Sources) -> Result<NodeRc, Diagnostics> {
+        let yaml: Yaml<RcRepr> = match n.yaml() {
+            Yaml::Seq(s) => s
+                .iter()
+                .map(|n| self.interpolate(n, src))
+                .collect::<Result<_, _>>()?,
+            Yaml::Map(m) => m
+                .iter()
+                .map(|(k, v)| match k.as_str() {
+                    Ok(key) if Self::FIELDS.contains(&key) => Ok((k.clone(), self.field(v, src)?)),
+                    _ => Ok((k.clone(), self.interpolate(v, src)?)),
+                })
+                .collect::<Result<_, Diagnostics>>()?,
+            _ => return Ok(n.clone()),
+        };
+        Ok(NodeRc::new(yaml, n.pos(), n.tag()))
+    }
+
+    fn field(&self, n: &NodeRc, src: &Sources) -> Result<NodeRc, Diagnostics> {
+        let yaml: Yaml<RcRepr> = match n.yaml() {
+            Yaml::Str(s) => Yaml::Str(
+                self.text(s)
+                    .map_err(|(msg, i)| Diagnostic::at(msg, src.scalar_pos(n.pos(), s, i)))?,
+            ),
+            Yaml::Seq(s) => s
+                .iter()
+                .map(|n| self.field(n, src))
+                .collect::<Result<_, _>>()?,
+            _ => return Ok(n.clone()),
+        };
+        Ok(NodeRc::new(yaml, n.pos(), n.tag()))
+    }
+
+    /// Replace the `{{name}}` variables, the code is skipped.
+    ///
+    /// The error has the byte offset of the braces.
+    fn text(&self, s: &str) -> Result<String, (String, usize)> {
+        let code = code_ranges(s);
+        let mut text = String::with_capacity(s.len());
+        let mut last = 0;
+        let mut i = 0;
+        while let Some(j) = s[i..].find("{{") {
+            let start = i + j;
+            if let Some(r) = code.iter().find(|r| r.contains(&start)) {
+                i = r.end;
+                continue;
+            }
+            if s[..start].ends_with('\\') {
+                // Escaped braces
+                text += &s[last..start - 1];
+                text += "{{";
+                i = start + 2;
+                last = i;
+                continue;
+            }
+            text += &s[last..start];
+            let (name, rest) = s[start + 2..].split_once("}}").ok_or_else(|| {
+                let msg = "unclosed variable, use `\\{{` for literal braces";
+                (msg.to_string(), start)
+            })?;
+            let name = name.trim();
+            let value = self
+                .vars
+                .get(name)
+                .ok_or_else(|| (format!("undefined variable `{name}`"), start))?;
+            text += value;
+            i = s.len() - rest.len();
+            last = i;
+        }
+        text += &s[last..];
+        Ok(text)
+    }
+}
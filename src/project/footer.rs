@@ -1,7 +1,7 @@
 use super::*;
 
 /// Global footer option.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct Footer {
     /// Footer text.
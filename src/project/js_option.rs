@@ -23,7 +23,7 @@ fn lower_camelcase(doc: &str) -> String {
 /// Other Reveal.js [options](https://revealjs.com/config/).
 ///
 /// + Use any cased string to indicate the option; this function will translate
-/// into lower camelcase. Those formats are allowed:
+///   into lower camelcase. Those formats are allowed:
 ///
 ///   + `slide-number`
 ///   + `slide number`
@@ -31,11 +31,11 @@ fn lower_camelcase(doc: &str) -> String {
 ///   + `slideNumber`
 ///
 /// + This place is actually what `Reveal.initialize` input. So plugin options
-/// should be placed here.
+///   should be placed here.
 ///
 /// + Use `!!markdown` type on the string type, let us help you convert from
-/// Markdown to HTML simply!
-#[derive(Default, serde::Deserialize)]
+///   Markdown to HTML simply!
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct JsOption {
     /// Inner data structure. (*flatten*)
@@ -60,7 +60,7 @@ impl ToHtml for JsOption {
 }
 
 /// The union type of the options.
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, schemars::JsonSchema)]
 #[serde(untagged)]
 pub enum JsType {
     /// Boolean values.
@@ -8,7 +8,7 @@ use std::{collections::HashMap, fmt::Write};
 /// + There is a repo demonstrate how to use [reveal.js-menu](https://github.com/denehyg/reveal.js-menu) plugin:
 ///   [Reveal.yaml-menu](https://github.com/KmolYuan/reveal.yaml-menu).
 /// + [Here](https://github.com/hakimel/reveal.js/wiki/Plugins,-Tools-and-Hardware#plugins) is the plugin list recommend by official.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct JsPlugin {
     /// Inner data structure. (*flatten*)
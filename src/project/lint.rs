@@ -0,0 +1,119 @@
+use super::*;
+use schemars::{schema_for, JsonSchema};
+use serde_json::{Map as JsonMap, Value};
+use yaml_peg::Yaml;
+
+/// Find the closest field name, the dash / underscore variants and the cases
+/// are treated as the same.
+fn suggest<'a>(key: &str, props: &'a JsonMap<String, Value>) -> Option<&'a str> {
+    let normalize = |s: &str| s.to_lowercase().replace('_', "-");
+    let key = normalize(key);
+    props
+        .keys()
+        .map(|p| {
+            (
+                strsim::normalized_damerau_levenshtein(&key, &normalize(p)),
+                p,
+            )
+        })
+        .filter(|(score, _)| *score >= 0.6)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, p)| p.as_str())
+}
+
+/// The unknown keys checker of the strict mode, using the JSON schema of the
+/// type.
+pub(crate) struct Linter(Value);
+
+impl Linter {
+    pub(crate) fn new<T: JsonSchema>() -> Self {
+        Self(schema_for!(T).to_value())
+    }
+
+    /// Report the unknown keys of the node as warnings.
+    pub(crate) fn lint(&self, n: &NodeRc, diags: &mut Vec<Diagnostic>) {
+        self.check(n, &self.0, diags);
+    }
+
+    fn resolve<'a>(&'a self, mut schema: &'a Value) -> &'a Value {
+        while let Some(name) = schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix("#/$defs/"))
+        {
+            match self.0.get("$defs").and_then(|defs| defs.get(name)) {
+                Some(s) => schema = s,
+                None => break,
+            }
+        }
+        schema
+    }
+
+    /// Return true if the schema is a map or a sequence like the node.
+    fn same_kind(&self, n: &NodeRc, schema: &Value) -> bool {
+        let ty = schema.get("type").and_then(Value::as_str);
+        match n.yaml() {
+            Yaml::Map(_) => {
+                ty == Some("object")
+                    || schema.get("properties").is_some()
+                    || schema.get("anyOf").is_some()
+            }
+            Yaml::Seq(_) => ty == Some("array") || schema.get("anyOf").is_some(),
+            _ => false,
+        }
+    }
+
+    fn check(&self, n: &NodeRc, schema: &Value, diags: &mut Vec<Diagnostic>) {
+        let schema = self.resolve(schema);
+        if let Some(branches) = schema
+            .get("anyOf")
+            .or_else(|| schema.get("oneOf"))
+            .and_then(Value::as_array)
+        {
+            // Choose the variant with the fewest warnings
+            let best = branches
+                .iter()
+                .map(|s| self.resolve(s))
+                .filter(|s| self.same_kind(n, s))
+                .map(|s| {
+                    let mut diags = Vec::new();
+                    self.check(n, s, &mut diags);
+                    diags
+                })
+                .min_by_key(Vec::len);
+            diags.extend(best.unwrap_or_default());
+            return;
+        }
+        match n.yaml() {
+            Yaml::Map(map) => {
+                let props = schema.get("properties").and_then(Value::as_object);
+                let extra = schema.get("additionalProperties");
+                for (k, v) in map {
+                    let key = k.as_str().unwrap_or_default();
+                    match (props.and_then(|props| props.get(key)), extra) {
+                        (Some(s), _) => self.check(v, s, diags),
+                        (None, Some(Value::Bool(false))) | (None, None) => {
+                            let Some(props) = props else {
+                                continue;
+                            };
+                            let d = Diagnostic::at(format!("unknown key `{key}`"), k.pos());
+                            let hint = suggest(key, props)
+                                .map(|p| format!("did you mean `{p}`?"))
+                                .unwrap_or_default();
+                            diags.push(Diagnostic { hint, warning: true, ..d });
+                        }
+                        (None, Some(s)) => self.check(v, s, diags),
+                    }
+                }
+            }
+            Yaml::Seq(seq) => {
+                if let Some(items) = schema.get("items") {
+                    for n in seq {
+                        self.check(n, items, diags);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
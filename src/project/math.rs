@@ -0,0 +1,350 @@
+use super::*;
+use pulldown_latex::{
+    config::DisplayMode, push_mathml, Event, Parser, ParserError, RenderConfig, Storage,
+};
+use std::{collections::HashMap, fmt::Write};
+
+const MATHJAX3: &str = "https://cdn.jsdelivr.net/npm/mathjax@3/es5/tex-mml-chtml.js";
+const OPEN: char = '\u{e000}';
+const CLOSE: char = '\u{e001}';
+
+/// Remove the first `\cmd{arg}` command and return the argument.
+fn take_arg(tex: &mut String, cmd: &str) -> Option<String> {
+    let (head, tail) = tex.split_once(cmd)?;
+    let (arg, tail) = tail.split_once('}')?;
+    let arg = arg.trim().to_string();
+    *tex = format!("{head}{tail}");
+    Some(arg)
+}
+
+/// Math renderers.
+#[derive(Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MathRenderer {
+    /// Client-side MathJax 2, the default renderer.
+    #[default]
+    MathJax2,
+    /// Client-side MathJax 3.
+    MathJax3,
+    /// Client-side KaTeX.
+    KaTeX,
+    /// Build-time MathML, no JavaScript required.
+    MathML,
+}
+
+/// Math typesetting option.
+///
+/// ```yaml
+/// math:
+///   renderer: mathml
+///   numbering: true
+///   macros:
+///     R: \mathbb{R}
+///     norm: \left\| #1 \right\|
+/// ```
+///
+/// The `mathml` renderer translates the `math` blocks and the `$...$` /
+/// `$$...$$` math in Markdown at build time. The invalid TeX is an error.
+#[derive(Default, Clone, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct MathOption {
+    /// Math renderer, `mathjax2`, `mathjax3`, `katex` or `mathml`.
+    ///
+    /// Only MathJax 2 and MathML are available in the offline mode.
+    pub renderer: MathRenderer,
+    /// TeX macros, the name is written without the backslash. The number of
+    /// the arguments is decided by the largest `#n` parameter.
+    pub macros: HashMap<String, String>,
+    /// Number the display equations across the deck, excluding the equations
+    /// with `\notag` or `\nonumber`. Use `\tag{...}` to give a custom number.
+    ///
+    /// The equations with `\label{eq:name}` are always numbered, and can be
+    /// referred as `@eq:name` in Markdown. The numbers are given at build
+    /// time, so the `$$...$$` math in Markdown is numbered only with the
+    /// `mathml` renderer or [`MarkdownOption::math`].
+    pub numbering: bool,
+}
+
+impl MathOption {
+    fn macros(&self) -> impl Iterator<Item = (&str, &str, usize)> {
+        self.macros.iter().map(|(name, body)| {
+            let args = (1..=9)
+                .rev()
+                .find(|n| body.contains(&format!("#{n}")))
+                .unwrap_or_default();
+            (name.trim_start_matches('\\'), body.as_str(), args)
+        })
+    }
+
+    /// Reveal plugin and its configuration.
+    pub(crate) fn plugin(&self, mathjax: &str) -> (String, String) {
+        let mut macros = String::new();
+        for (name, body, args) in self.macros() {
+            match self.renderer {
+                MathRenderer::KaTeX => write!(macros, "{:?}: {body:?}, ", format!("\\{name}")),
+                _ if args == 0 => write!(macros, "{name:?}: {body:?}, "),
+                _ => write!(macros, "{name:?}: [{body:?}, {args}], "),
+            }
+            .unwrap();
+        }
+        let (plugin, config) = match self.renderer {
+            MathRenderer::MathJax2 => (
+                "RevealMath.MathJax2",
+                format!(
+                    "mathjax2: {{
+            mathjax: '{mathjax}',
+            config: 'TeX-AMS_HTML-full',
+            tex2jax: {{
+                inlineMath: [['$', '$'], ['\\\\(', '\\\\)']],
+                skipTags: ['script', 'noscript', 'style', 'textarea', 'pre']
+            }},
+            TeX: {{Macros: {{{macros}}}, equationNumbers: {{autoNumber: 'none'}}}}
+        }},"
+                ),
+            ),
+            MathRenderer::MathJax3 => (
+                "RevealMath.MathJax3",
+                format!(
+                    "mathjax3: {{
+            mathjax: '{MATHJAX3}',
+            tex: {{
+                inlineMath: [['$', '$'], ['\\\\(', '\\\\)']],
+                macros: {{{macros}}},
+                tags: 'none'
+            }},
+            options: {{skipHtmlTags: ['script', 'noscript', 'style', 'textarea', 'pre']}}
+        }},"
+                ),
+            ),
+            MathRenderer::KaTeX => (
+                "RevealMath.KaTeX",
+                format!(
+                    "katex: {{
+            version: 'latest',
+            delimiters: [
+                {{left: '$$', right: '$$', display: true}},
+                {{left: '$', right: '$', display: false}},
+                {{left: '\\\\(', right: '\\\\)', display: false}},
+                {{left: '\\\\[', right: '\\\\]', display: true}}
+            ],
+            ignoredTags: ['script', 'noscript', 'style', 'textarea', 'pre'],
+            macros: {{{macros}}}
+        }},"
+                ),
+            ),
+            MathRenderer::MathML => return Default::default(),
+        };
+        (plugin.to_string() + ", ", config)
+    }
+
+    /// Render the math. The client-side renderers only add the brackets and
+    /// the equation number.
+    pub(crate) fn render(&self, tex: &str, display: bool, ctx: &Ctx) -> String {
+        if tex.trim().is_empty() {
+            return String::new();
+        }
+        let mut tex = tex.to_string();
+        let tag = take_arg(&mut tex, "\\tag{");
+        let label = take_arg(&mut tex, "\\label{");
+        let notag = ["\\notag", "\\nonumber"]
+            .into_iter()
+            .any(|cmd| tex.contains(cmd));
+        if notag {
+            tex = tex.replace("\\notag", "").replace("\\nonumber", "");
+        }
+        if label.is_some() && !display {
+            ctx.error(format!(
+                "label of the inline math `{}` is not allowed",
+                tex.trim()
+            ));
+        }
+        let no = if display && (self.numbering && !notag || label.is_some()) || tag.is_some() {
+            let no = tag.unwrap_or_else(|| Ctx::count(&ctx.eq).to_string());
+            ctx.label(&label.unwrap_or_default(), "eq:", &no);
+            Some(no)
+        } else {
+            None
+        };
+        if self.renderer != MathRenderer::MathML {
+            if let Some(no) = no {
+                tex += &format!("\\tag{{{no}}}");
+            }
+            return if display {
+                tex.wrap("\\[", "\\]")
+            } else {
+                tex.wrap("\\(", "\\)")
+            };
+        }
+        let mut preamble = String::new();
+        for (name, body, args) in self.macros() {
+            write!(preamble, "\\def\\{name}").unwrap();
+            for i in 1..=args {
+                write!(preamble, "#{i}").unwrap();
+            }
+            write!(preamble, "{{{body}}}").unwrap();
+        }
+        let input = preamble + &tex;
+        let storage = Storage::new();
+        let events = match Parser::new(&input, &storage).collect::<Result<Vec<_>, _>>() {
+            Ok(events) => events,
+            Err(e) => {
+                ctx.error(format!("invalid TeX `{}`: {e}", tex.trim()));
+                return String::new();
+            }
+        };
+        let config = RenderConfig {
+            display_mode: if display {
+                DisplayMode::Block
+            } else {
+                DisplayMode::Inline
+            },
+            ..Default::default()
+        };
+        let mut s = String::new();
+        push_mathml(
+            &mut s,
+            events.into_iter().map(Ok::<Event, ParserError>),
+            config,
+        )
+        .unwrap();
+        match no {
+            Some(no) => format!(
+                "<span class=\"math-eq\">{s}<span class=\"math-eq-no\">({no})</span></span>"
+            ),
+            None => s,
+        }
+    }
+}
+
+/// Replace the `$...$` and `$$...$$` math in Markdown with placeholders, the
+/// code blocks and the code spans are skipped.
+///
+/// Returns the replaced text and the math list with display mode.
+pub(crate) fn extract_math(text: &str) -> (String, Vec<(String, bool)>) {
+    let mut s = String::with_capacity(text.len());
+    let mut maths = Vec::new();
+    let mut fence = None;
+    let mut para = String::new();
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+        match (fence, marker) {
+            (None, Some(m)) => {
+                s += &extract_para(&para, &mut maths);
+                para.clear();
+                fence = Some(m);
+                s += line;
+            }
+            (Some(f), Some(m)) if f == m => {
+                fence = None;
+                s += line;
+            }
+            (Some(_), _) => s += line,
+            (None, None) => para += line,
+        }
+    }
+    s += &extract_para(&para, &mut maths);
+    (s, maths)
+}
+
+fn extract_para(text: &str, maths: &mut Vec<(String, bool)>) -> String {
+    let mut s = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut push = |tex: &str, display: bool, s: &mut String| {
+        write!(s, "{OPEN}{}{CLOSE}", maths.len()).unwrap();
+        // The escaped star of the old syntax
+        maths.push((tex.replace("\\*", "*"), display));
+    };
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '\\' => {
+                let n = rest[1..].chars().next().map_or(0, char::len_utf8);
+                s += &rest[..1 + n];
+                rest = &rest[1 + n..];
+            }
+            '`' => {
+                let n = rest.len() - rest.trim_start_matches('`').len();
+                let ticks = &rest[..n];
+                let end = rest[n..]
+                    .match_indices(ticks)
+                    .find(|(i, _)| !rest[n + i + n..].starts_with('`'))
+                    .map_or(n, |(i, _)| n + i + n);
+                s += &rest[..end];
+                rest = &rest[end..];
+            }
+            '$' if rest.starts_with("$$") => match rest[2..].find("$$") {
+                Some(i) => {
+                    push(&rest[2..2 + i], true, &mut s);
+                    rest = &rest[i + 4..];
+                }
+                None => {
+                    s += "$$";
+                    rest = &rest[2..];
+                }
+            },
+            '$' => match inline_end(&rest[1..]) {
+                Some(i) => {
+                    push(&rest[1..1 + i], false, &mut s);
+                    rest = &rest[i + 2..];
+                }
+                None => {
+                    s.push('$');
+                    rest = &rest[1..];
+                }
+            },
+            c => {
+                s.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    s
+}
+
+/// Find the end of the inline math, the content cannot start or end with
+/// spaces, and the closing `$` cannot be followed by a digit.
+fn inline_end(text: &str) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '$' if i > 0
+                && !text[..i].ends_with(char::is_whitespace)
+                && !text[i + 1..].starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                return Some(i)
+            }
+            '$' => return None,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Replace the placeholders with the rendered math.
+pub(crate) fn restore_math(html: &str, maths: &[String]) -> String {
+    let mut s = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(i) = rest.find(OPEN) {
+        s += &rest[..i];
+        let tail = &rest[i + OPEN.len_utf8()..];
+        match tail
+            .split_once(CLOSE)
+            .and_then(|(n, tail)| Some((maths.get(n.parse::<usize>().ok()?)?, tail)))
+        {
+            Some((math, tail)) => {
+                s += math;
+                rest = tail;
+            }
+            None => {
+                s.push(OPEN);
+                rest = tail;
+            }
+        }
+    }
+    s + rest
+}
@@ -1,17 +1,21 @@
 use super::*;
-use yaml_peg::serde::Optional;
+use crate::update::mathjax;
+use std::collections::{HashMap, HashSet};
+use yaml_peg::serde::{InlineList, Optional, Stringify};
 
 const TEMPLATE: &str = include_str!("../assets/template.html");
 const RELOAD: &str = "\
 let ws = new WebSocket(\"ws://\" + window.location.host + \"/ws/\");
             ws.onmessage = _ => location.reload();";
+const MATHJAX: &str = "https://cdn.jsdelivr.net/npm/mathjax@2/MathJax.js";
+const REQUIRED: &[&str] = &["slides", "option", "plugin", "plugin-files", "auto-reload"];
 
 /// Metadata contains HTML settings and global slide settings, they are totally
 /// YAML Maps.
 ///
 /// The definition contains in the first YAML doc, split by horizontal line
 /// `---`.
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct Metadata {
     /// Webpage icon path, defaults to "img/favicon.png".
@@ -22,8 +26,40 @@ pub struct Metadata {
     pub title: String,
     /// Webpage description.
     pub description: String,
-    /// Webpage author.
+    /// Webpage author, defaults to the names of `authors`.
     pub author: String,
+    /// Subtitle of the generated title slide.
+    pub subtitle: String,
+    /// Authors of the generated title slide, see [`Author`].
+    pub authors: Vec<Author>,
+    /// Date of the generated title slide, `today` for the date of the build.
+    pub date: String,
+    /// Event name of the generated title slide, such as the conference.
+    pub event: String,
+    /// Logos of the generated title slide.
+    #[schemars(with = "schema::InlineList<Sized>")]
+    pub logo: InlineList<Sized>,
+    /// Generate the title slide as the first slide with the layout, `none` by
+    /// default.
+    ///
+    /// ```yaml
+    /// title: Reveal.yaml
+    /// subtitle: Slides in YAML
+    /// authors:
+    ///   - name: Alice
+    ///     affiliation: Example University
+    /// date: today
+    /// event: RustConf
+    /// logo:
+    ///   src: img/icon.png
+    ///   height: 100
+    /// title-slide: center
+    /// ```
+    ///
+    /// The layouts are `center`, `left` and `banner`, the title slide has an
+    /// id "title".
+    #[serde(rename = "title-slide")]
+    pub title_slide: TitleLayout,
     /// Global background setting.
     ///
     /// For local option, please see [`Slide::background`].
@@ -39,15 +75,27 @@ pub struct Metadata {
     /// Auto generated table of the contents (TOC), `true` by default.
     ///
     /// To specify the outline title, just provide a string,
-    /// a markdown level 1 title without `#` notation.
+    /// a markdown level 1 title without `#` notation. For more options,
+    /// please see [`Outline`].
     ///
     /// Outline page has an id "outline".
-    pub outline: Optional<String>,
+    #[schemars(with = "schema::Optional<Outline>")]
+    pub outline: Optional<Outline>,
     /// Reveal.js theme, "serif" by default.
     pub theme: String,
     /// Highlight theme, "zenburn" by default.
+    ///
+    /// For the build-time highlighter, please see [`Syntect::new`].
     #[serde(rename = "code-theme")]
     pub code_theme: String,
+    /// Code highlighter, `highlight.js` or `syntect`.
+    ///
+    /// The `syntect` highlighter highlights the code blocks at build time, so
+    /// the code is highlighted without JavaScript.
+    pub highlighter: CodeHighlighter,
+    /// The folder of the extra `.sublime-syntax` files for the `syntect`
+    /// highlighter.
+    pub syntaxes: String,
     /// Extra CSS script in `<style>` tag.
     pub style: String,
     /// Global footer option. You can add your logo here.
@@ -56,6 +104,73 @@ pub struct Metadata {
     pub option: JsOption,
     /// Third-party Reveal plugins.
     pub plugin: JsPlugin,
+    /// Math renderer and TeX macros.
+    pub math: MathOption,
+    /// Markdown extensions.
+    pub markdown: MarkdownOption,
+    /// BibTeX / BibLaTeX file path for the citations.
+    pub bibliography: String,
+    /// Citation style and the references slide.
+    pub citation: CitationOption,
+    /// Reusable slide templates.
+    ///
+    /// A template is a partial slide or content block with `${name}`
+    /// placeholders. Use it by the `use` key, and give the values by the `with`
+    /// key. Other keys beside the `use` key will override the template.
+    ///
+    /// ```yaml
+    /// templates:
+    ///   divider:
+    ///     title: ${title}
+    ///     class: divider
+    ///     doc: Chapter ${no}
+    /// ---
+    /// - use: divider
+    ///   with: { title: Introduction, no: 1 }
+    ///   note: Start here!
+    /// ```
+    ///
+    /// + If a string only has a placeholder, it can be replaced by a map or a
+    ///   sequence.
+    /// + Use `$${` to write a literal `${`.
+    /// + The placeholder without a value is an error.
+    #[schemars(with = "HashMap<String, schema::Any>")]
+    pub templates: HashMap<String, NodeRc>,
+    /// Project variables, which can be used in the title, `doc`, `note`,
+    /// `fit`, the footer label and `src` options by `{{name}}` syntax.
+    ///
+    /// ```yaml
+    /// title: "{{course}} Lecture 3"
+    /// vars:
+    ///   course: CS101
+    ///   semester: Fall 2022
+    /// ---
+    /// - title: "{{course}}"
+    ///   doc: |
+    ///     Welcome to {{course}} ({{ semester }})!
+    ///
+    ///     Write \{{name}} for the literal braces.
+    /// ```
+    ///
+    /// The undefined variable is an error, even if there are no variables. The
+    /// code blocks and the inline code are not interpolated.
+    #[schemars(with = "HashMap<String, schema::Stringify>")]
+    pub vars: HashMap<String, Stringify>,
+    /// Custom HTML template path, the built-in template by default.
+    ///
+    /// The placeholders are written as `{%name}`, and the following are
+    /// supported: `icon`, `lang`, `title`, `description`, `author`, `theme`,
+    /// `code-theme`, `footer`, `slides`, `auto-reload`, `option`, `style`,
+    /// `plugin`, `plugin-files`, `mount`, `mathjax` (the MathJax 2 script
+    /// URL), `math` (the math plugin options) and `math-plugin`.
+    ///
+    /// A placeholder can be wrapped with `/* */` or `<!-- -->` comments to keep
+    /// the template valid. The `slides`, `option`, `plugin`, `plugin-files` and
+    /// `auto-reload` placeholders are required.
+    pub template: String,
+    /// Report the unknown keys as warnings, with the suggestions of the
+    /// closest keys. Also enabled by the `--strict` flag.
+    pub strict: bool,
 }
 
 impl Default for Metadata {
@@ -66,46 +181,110 @@ impl Default for Metadata {
             title: String::new(),
             description: String::new(),
             author: String::new(),
+            subtitle: String::new(),
+            authors: Vec::new(),
+            date: String::new(),
+            event: String::new(),
+            logo: InlineList::default(),
+            title_slide: TitleLayout::default(),
             background: Background::default(),
             auto_animate: false,
             chapter_header: false,
             outline: Optional::Bool(true),
             theme: "serif".to_string(),
             code_theme: "zenburn".to_string(),
+            highlighter: CodeHighlighter::default(),
+            syntaxes: String::new(),
             style: String::new(),
             footer: Footer::default(),
             option: JsOption::default(),
             plugin: JsPlugin::default(),
+            math: MathOption::default(),
+            markdown: MarkdownOption::default(),
+            bibliography: String::new(),
+            citation: CitationOption::default(),
+            templates: HashMap::new(),
+            vars: HashMap::new(),
+            template: String::new(),
+            strict: false,
         }
     }
 }
 
 impl Metadata {
     /// Build HTML from template.
-    pub fn build(self, slides: Slides, mount: &str, auto_reload: bool) -> String {
+    pub fn build(self, slides: Slides, mount: &str, auto_reload: bool) -> Result<String, IoError> {
+        self.render(
+            slides,
+            Path::new(""),
+            mount,
+            auto_reload,
+            false,
+            &mut Vec::new(),
+        )
+        .map_err(|diags| Diagnostics(diags).into())
+    }
+
+    /// Build HTML from template, and collect the files that are read.
+    ///
+    /// The offline mode loads MathJax from the mount point instead of CDN. The
+    /// local files are read from the project directory `base`.
+    pub(crate) fn render(
+        self,
+        slides: Slides,
+        base: &Path,
+        mount: &str,
+        auto_reload: bool,
+        offline: bool,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<String, Vec<Diagnostic>> {
         let Self {
             icon,
             lang,
             title,
             description,
             author,
+            subtitle,
+            authors,
+            date,
+            event,
+            logo,
+            title_slide,
             background,
             auto_animate,
             chapter_header,
             outline,
             theme,
             code_theme,
+            highlighter,
+            syntaxes,
             style,
             footer,
             option,
             plugin,
+            math,
+            markdown,
+            bibliography,
+            citation,
+            templates: _,
+            vars: _,
+            template,
+            strict: _,
         } = self;
-        let outline = match outline {
-            Optional::Bool(true) => "Outline".to_string(),
-            Optional::Bool(false) => String::new(),
-            Optional::Some(outline) => outline,
+        let outline = OutlineOption::new(outline);
+        let mathjax = if offline {
+            format!(concat!("{}", mathjax!(), "/MathJax.js"), mount)
+        } else {
+            MATHJAX.to_string()
         };
-        let ctx = Ctx {
+        let (math_plugin, math_config) = math.plugin(&mathjax);
+        let syntect = match highlighter {
+            CodeHighlighter::HighlightJs => None,
+            CodeHighlighter::Syntect => Some(
+                Syntect::new(&code_theme, &syntaxes, base).map_err(|e| vec![Diagnostic::new(e)])?,
+            ),
+        };
+        let mut ctx = Ctx {
             outline,
             auto_animate,
             chapter_header: if chapter_header {
@@ -114,29 +293,135 @@ impl Metadata {
                 None
             },
             background: background.to_html(&Default::default()),
-            frag: Default::default(),
+            math,
+            markdown,
+            syntect,
+            base: base.to_path_buf(),
+            ..Default::default()
+        };
+        if offline {
+            let renderer = match ctx.math.renderer {
+                MathRenderer::MathJax3 => "mathjax3",
+                MathRenderer::KaTeX => "katex",
+                _ => "",
+            };
+            if !renderer.is_empty() {
+                ctx.error(format!(
+                    "math renderer `{renderer}` is not available in the offline mode, \
+                     use `mathjax2` or `mathml`"
+                ));
+            }
+        }
+        if !bibliography.is_empty() {
+            ctx.bib = ctx.read(&bibliography).and_then(|src| {
+                Bib::new(&src, citation)
+                    .map_err(|e| ctx.error(format!("{bibliography}: {e}")))
+                    .ok()
+            });
+        }
+        let title_slide = TitleSlide {
+            layout: title_slide,
+            title: &title,
+            subtitle: &subtitle,
+            authors: &authors,
+            date: &date,
+            event: &event,
+            logo,
         };
+        let mut slides = slides;
+        if let Some(chapter) = title_slide.into_slide() {
+            slides.slides.insert(0, chapter);
+        }
         let title = match (title.as_str(), slides.slides.first()) {
-            ("", Some(chapter)) => &chapter.slide.title,
-            (title, _) => title,
+            ("", Some(chapter)) => chapter.slide.title.clone(),
+            (title, _) => title.to_string(),
+        };
+        let author = if author.is_empty() {
+            authors
+                .iter()
+                .map(Author::name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        } else {
+            author
         };
         let auto_reload = if auto_reload { RELOAD } else { "" };
         let (plugin_names, plugin_files) = plugin.name_and_files();
-        TEMPLATE
-            .replace("{%icon}", &icon)
-            .replace("{%lang}", &lang)
-            .replace("{%title}", &title.escape())
-            .replace("{%description}", &description.escape())
-            .replace("{%author}", &author.escape())
-            .replace("{%theme}", &theme)
-            .replace("{%code-theme}", &code_theme)
-            .replace("{%footer}", &footer.to_html(&ctx))
-            .replace("{%slides}", &slides.to_html(&ctx))
-            .replace("/* {%auto-reload} */", auto_reload)
-            .replace("/* {%option} */", &option.to_html(&ctx))
-            .replace("/* {%style} */", &style)
-            .replace("/* {%plugin} */", &plugin_names)
-            .replace("<!-- {%plugin} -->", &plugin_files)
-            .replace("{%mount}", mount)
+        let slides = slides.to_html(&ctx);
+        let footer = footer.to_html(&ctx);
+        let option = option.to_html(&ctx);
+        let template = if template.is_empty() {
+            None
+        } else {
+            ctx.read(&template).map(|doc| (template, doc))
+        };
+        let Ctx { errors, files: ctx_files, .. } = ctx;
+        files.extend(ctx_files.into_inner());
+        let errors = errors.into_inner();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let slots = HashMap::from([
+            ("icon", icon),
+            ("lang", lang),
+            ("title", title.escape()),
+            ("description", description.escape()),
+            ("author", author.escape()),
+            ("theme", theme),
+            ("code-theme", code_theme),
+            ("footer", footer),
+            ("slides", slides),
+            ("auto-reload", auto_reload.to_string()),
+            ("option", option),
+            ("style", style),
+            ("plugin", plugin_names),
+            ("plugin-files", plugin_files),
+            ("mount", mount.to_string()),
+            ("mathjax", mathjax),
+            ("math", math_config),
+            ("math-plugin", math_plugin),
+        ]);
+        match template {
+            Some((path, doc)) => {
+                fill(&doc, &slots).map_err(|e| vec![Diagnostic::new(format!("{path}: {e}"))])
+            }
+            None => Ok(fill(TEMPLATE, &slots).expect("built-in template")),
+        }
+    }
+}
+
+/// Fill the placeholders of the HTML template in single pass, so the inserted
+/// contents will never be scanned again.
+///
+/// The placeholders can be wrapped with comments, such as `/* {%option} */` and
+/// `<!-- {%plugin-files} -->`, the comment will be replaced together.
+fn fill(template: &str, slots: &HashMap<&str, String>) -> Result<String, String> {
+    let mut s = String::with_capacity(template.len());
+    let mut used = HashSet::new();
+    let mut rest = template;
+    while let Some(i) = rest.find("{%") {
+        let (name, mut tail) = rest[i + 2..]
+            .split_once('}')
+            .ok_or_else(|| "unclosed placeholder `{%`".to_string())?;
+        let value = slots
+            .get(name)
+            .ok_or_else(|| format!("unknown placeholder `{{%{name}}}`"))?;
+        let mut head = &rest[..i];
+        for (open, close) in [("/* ", " */"), ("<!-- ", " -->")] {
+            if let (Some(h), Some(t)) = (head.strip_suffix(open), tail.strip_prefix(close)) {
+                head = h;
+                tail = t;
+                break;
+            }
+        }
+        s += head;
+        s += value;
+        used.insert(name);
+        rest = tail;
+    }
+    s += rest;
+    match REQUIRED.iter().find(|name| !used.contains(*name)) {
+        Some(name) => Err(format!("missing placeholder `{{%{name}}}`")),
+        None => Ok(s),
     }
 }
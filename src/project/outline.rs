@@ -0,0 +1,208 @@
+use super::*;
+use yaml_peg::serde::Optional;
+
+/// Outline option, a title string or a map.
+///
+/// ```yaml
+/// outline: Contents
+/// ## or
+/// outline:
+///   title: Contents
+///   depth: 1
+///   numbering: true
+///   columns: 2
+///   place: after-title
+///   dividers: true
+/// ```
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum Outline {
+    /// The outline title.
+    Title(String),
+    /// The full option.
+    Option(OutlineOption),
+}
+
+/// The placement of the outline slide.
+#[derive(Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutlinePlace {
+    /// Under the first chapter (vertical), the default placement.
+    #[default]
+    FirstSub,
+    /// A chapter (horizontal) slide after the first chapter.
+    AfterTitle,
+    /// A chapter (horizontal) slide at the end.
+    End,
+}
+
+/// The structured outline option, see [`Outline`].
+#[derive(Clone, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct OutlineOption {
+    /// Outline title, a markdown level 1 title without `#` notation,
+    /// "Outline" by default.
+    pub title: String,
+    /// The levels of the outline, `1` for the chapters only, `2` by default
+    /// to include the sections.
+    pub depth: usize,
+    /// Number the chapters and the sections, such as "1" and "1.1". The
+    /// numbers are also added to the slide titles.
+    pub numbering: bool,
+    /// Number of the columns, `1` by default.
+    pub columns: usize,
+    /// Outline slide placement, `first-sub`, `after-title` or `end`.
+    pub place: OutlinePlace,
+    /// Add a section divider slide before each chapter, which shows the
+    /// outline with the current chapter highlighted.
+    pub dividers: bool,
+}
+
+impl Default for OutlineOption {
+    fn default() -> Self {
+        Self {
+            title: "Outline".to_string(),
+            depth: 2,
+            numbering: false,
+            columns: 1,
+            place: OutlinePlace::default(),
+            dividers: false,
+        }
+    }
+}
+
+/// An outline entry, the number, the title, the id and the sections.
+struct Entry {
+    no: String,
+    title: String,
+    id: String,
+    sub: Vec<Entry>,
+}
+
+impl OutlineOption {
+    /// Convert from the metadata, the empty title disables the outline.
+    pub(crate) fn new(outline: Optional<Outline>) -> Option<Self> {
+        let outline = match outline {
+            Optional::Bool(false) => return None,
+            Optional::Bool(true) => Self::default(),
+            Optional::Some(Outline::Title(title)) => Self { title, ..Self::default() },
+            Optional::Some(Outline::Option(outline)) => outline,
+        };
+        (!outline.title.is_empty()).then_some(outline)
+    }
+
+    fn entries(&self, slides: &[ChapterSlide]) -> Vec<(usize, Entry)> {
+        let entry = |no: String, slide: &Slide, sub| Entry {
+            no,
+            title: slide_title(slide).to_string(),
+            id: slide.id.clone(),
+            sub,
+        };
+        slides
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, chapter)| !slide_title(&chapter.slide).is_empty())
+            .enumerate()
+            .map(|(i, (h, chapter))| {
+                let no = (i + 1).to_string();
+                let sub = chapter
+                    .sub
+                    .iter()
+                    .filter(|slide| !slide_title(slide).is_empty())
+                    .enumerate()
+                    .map(|(j, slide)| entry(format!("{no}.{}", j + 1), slide, Vec::new()))
+                    .collect();
+                (h, entry(no, &chapter.slide, sub))
+            })
+            .collect()
+    }
+
+    fn doc(&self, entries: &[(usize, Entry)], current: Option<usize>) -> String {
+        let link = |e: &Entry| {
+            let no = if self.numbering {
+                format!("{} ", e.no)
+            } else {
+                String::new()
+            };
+            format!("[{no}{}](#/{})", e.title, e.id)
+        };
+        let mut doc = String::new();
+        for (h, e) in entries {
+            if current == Some(*h) {
+                doc += &format!("+ **{}**\n", link(e));
+            } else {
+                doc += &format!("+ {}\n", link(e));
+            }
+            if self.depth > 1 {
+                for e in &e.sub {
+                    doc += &format!("  + {}\n", link(e));
+                }
+            }
+        }
+        doc
+    }
+
+    fn slide(&self, doc: String, id: &str, class: &str) -> Slide {
+        let mut class = class.to_string();
+        if self.numbering {
+            class += " numbered";
+        }
+        let attr = if self.columns > 1 {
+            format!("style=\"--outline-columns: {}\"", self.columns)
+        } else {
+            String::new()
+        };
+        Slide {
+            title: self.title.clone(),
+            id: id.to_string(),
+            class,
+            attr,
+            content: Content { doc, ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    /// Insert the outline slide and the section dividers, and number the
+    /// slide titles.
+    pub(crate) fn apply(&self, slides: &mut Vec<ChapterSlide>) {
+        let entries = self.entries(slides);
+        if entries.is_empty() {
+            return;
+        }
+        if self.numbering {
+            let number = |slide: &mut Slide, no: &str| {
+                let title = if slide.title.is_empty() {
+                    &mut slide.title_hidden
+                } else {
+                    &mut slide.title
+                };
+                *title = format!("{no} {title}");
+            };
+            for (h, e) in &entries {
+                let chapter = &mut slides[*h];
+                number(&mut chapter.slide, &e.no);
+                let sub = chapter
+                    .sub
+                    .iter_mut()
+                    .filter(|slide| !slide_title(slide).is_empty());
+                for (slide, e) in sub.zip(&e.sub) {
+                    number(slide, &e.no);
+                }
+            }
+        }
+        if self.dividers {
+            for (h, _) in entries.iter().rev() {
+                let doc = self.doc(&entries, Some(*h));
+                let slide = self.slide(doc, "", "outline section-divider");
+                slides.insert(*h, ChapterSlide { slide, sub: Vec::new() });
+            }
+        }
+        let slide = self.slide(self.doc(&entries, None), "outline", "outline");
+        match self.place {
+            OutlinePlace::FirstSub => slides[0].sub.push(slide),
+            OutlinePlace::AfterTitle => slides.insert(1, ChapterSlide { slide, sub: Vec::new() }),
+            OutlinePlace::End => slides.push(ChapterSlide { slide, sub: Vec::new() }),
+        }
+    }
+}
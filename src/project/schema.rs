@@ -0,0 +1,120 @@
+use super::{ChapterSlide, Metadata};
+use schemars::{
+    generate::SchemaSettings, json_schema, transform::RecursiveTransform, JsonSchema, Schema,
+    SchemaGenerator,
+};
+use std::{borrow::Cow, marker::PhantomData};
+
+/// A project document, the metadata, a chapter slide, or a list of the
+/// chapter slides.
+struct Document;
+
+impl JsonSchema for Document {
+    fn schema_name() -> Cow<'static, str> {
+        "Reveal.yaml".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let slide = gen.subschema_for::<ChapterSlide>();
+        json_schema!({
+            "description": "A Reveal.yaml document, the metadata, a slide, or a list of the slides.",
+            "anyOf": [
+                gen.subschema_for::<Metadata>(),
+                slide,
+                {"type": "array", "items": slide},
+            ],
+        })
+    }
+}
+
+/// Remove the rustdoc link brackets from the descriptions.
+fn unlink(schema: &mut Schema) {
+    if let Some(serde_json::Value::String(s)) = schema.get_mut("description") {
+        *s = s.replace("[`", "`").replace("`]", "`");
+    }
+}
+
+/// The JSON schema (draft-07) of a project document.
+pub(crate) fn root() -> Schema {
+    SchemaSettings::draft07()
+        .with_transform(RecursiveTransform(unlink))
+        .into_generator()
+        .into_root_schema_for::<Document>()
+}
+
+/// The JSON schema (draft-07) of the project files.
+///
+/// Each YAML document is validated alone, so both the single-list form and
+/// the multi-document form are accepted.
+pub fn json_schema() -> String {
+    serde_json::to_string_pretty(&root()).unwrap()
+}
+
+/// A boolean or the value, see [`yaml_peg::serde::Optional`].
+pub(crate) struct Optional<T>(PhantomData<T>);
+
+impl<T: JsonSchema> JsonSchema for Optional<T> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        format!("Optional_{}", T::schema_name()).into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        json_schema!({"anyOf": [{"type": "boolean"}, gen.subschema_for::<T>()]})
+    }
+}
+
+/// A single item or a list, see [`yaml_peg::serde::InlineList`].
+pub(crate) struct InlineList<T>(PhantomData<T>);
+
+impl<T: JsonSchema> JsonSchema for InlineList<T> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        format!("InlineList_{}", T::schema_name()).into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let item = gen.subschema_for::<T>();
+        json_schema!({"anyOf": [item, {"type": "array", "items": item}]})
+    }
+}
+
+/// A scalar value, see [`yaml_peg::serde::Stringify`].
+pub(crate) struct Stringify;
+
+impl JsonSchema for Stringify {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Stringify".into()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        json_schema!({"type": ["string", "number", "boolean", "null"]})
+    }
+}
+
+/// Any YAML node.
+pub(crate) struct Any;
+
+impl JsonSchema for Any {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Any".into()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        true.into()
+    }
+}
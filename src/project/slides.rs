@@ -1,7 +1,8 @@
 use super::*;
+use std::collections::HashSet;
 use yaml_peg::serde::Optional;
 
-fn slide_title(slide: &Slide) -> &str {
+pub(crate) fn slide_title(slide: &Slide) -> &str {
     if !slide.title.is_empty() {
         &slide.title
     } else {
@@ -9,6 +10,65 @@ fn slide_title(slide: &Slide) -> &str {
     }
 }
 
+/// Slugify the title as an id.
+fn slug(title: &str) -> String {
+    let mut s = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            s.push(c);
+        } else if !s.is_empty() && !s.ends_with('-') {
+            s.push('-');
+        }
+    }
+    s.trim_end_matches('-').to_string()
+}
+
+/// Give the slides without the id a slug from the title, and collect the
+/// titles of the ids.
+///
+/// The duplicated ids are errors, and the duplicated slugs have a number
+/// suffix.
+pub(crate) fn assign_ids(slides: &mut [ChapterSlide], ctx: &Ctx) {
+    let mut used = HashSet::from(["outline".to_string(), "references".to_string()]);
+    let all = || {
+        slides
+            .iter()
+            .flat_map(|c| std::iter::once(&c.slide).chain(&c.sub))
+    };
+    for slide in all() {
+        if !slide.id.is_empty() && !used.insert(slide.id.clone()) {
+            ctx.pos.set(slide.pos);
+            ctx.error(format!("duplicated slide id `{}`", slide.id));
+        }
+    }
+    ctx.pos.set(None);
+    let mut titles = ctx.slides.borrow_mut();
+    for slide in slides
+        .iter_mut()
+        .flat_map(|c| std::iter::once(&mut c.slide).chain(&mut c.sub))
+    {
+        if slide.id.is_empty() {
+            let title = slide_title(slide);
+            if title.is_empty() {
+                continue;
+            }
+            let mut slug = slug(title);
+            if slug.is_empty() {
+                slug = "slide".to_string();
+            }
+            let mut id = slug.clone();
+            let mut n = 1;
+            while used.contains(&id) {
+                n += 1;
+                id = format!("{slug}-{n}");
+            }
+            used.insert(id.clone());
+            slide.id = id;
+        }
+        titles.insert(slide.id.clone(), slide_title(slide).to_string());
+    }
+}
+
 /// Slides data.
 ///
 /// Slides are a list of multiple slide blocks, they are totally YAML Maps.
@@ -25,6 +85,11 @@ fn slide_title(slide: &Slide) -> &str {
 ///
 /// Only chapter (horizontal) slides has "sub" attribute,
 /// which can append section slides vertically.
+///
+/// The slides without [`Slide::id`] have the ids from their titles, such as
+/// "title-2-1". Link to a slide in Markdown by `[[title-2-1]]` or
+/// `[[title-2-1|custom text]]`. The brackets are left as text if the id has
+/// the characters other than letters, digits, `-` and `_`, or is a number.
 pub struct Slides {
     /// The slides.
     pub slides: Vec<ChapterSlide>,
@@ -52,58 +117,33 @@ impl Slides {
 impl ToHtml for Slides {
     fn to_html(self, ctx: &Ctx) -> String {
         let Self { mut slides } = self;
-        if !ctx.outline.is_empty() && slides.len() > 1 {
-            let doc = slides
-                .iter()
-                .enumerate()
-                .skip(1)
-                .map(|(i, chapter)| {
-                    let title = slide_title(&chapter.slide);
-                    if title.is_empty() {
-                        String::new()
-                    } else {
-                        let id = if chapter.slide.id.is_empty() {
-                            i.to_string()
-                        } else {
-                            chapter.slide.id.clone()
-                        };
-                        format!("+ [{title}](#/{id})\n")
-                            + &chapter
-                                .sub
-                                .iter()
-                                .enumerate()
-                                .map(|(j, slide)| {
-                                    let title = slide_title(slide);
-                                    if !title.is_empty() {
-                                        let id = if slide.id.is_empty() {
-                                            format!("{i}/{}", j + 1)
-                                        } else {
-                                            slide.id.clone()
-                                        };
-                                        format!("  + [{title}](#/{id})\n")
-                                    } else {
-                                        String::new()
-                                    }
-                                })
-                                .collect::<String>()
-                    }
-                })
-                .collect();
-            slides.first_mut().unwrap().sub.push(Slide {
-                title: ctx.outline.clone(),
-                id: "outline".to_string(),
-                content: Content { doc, ..Default::default() },
-                ..Default::default()
-            });
+        assign_ids(&mut slides, ctx);
+        if let Some(outline) = &ctx.outline {
+            outline.apply(&mut slides);
         }
-        slides.to_html(ctx)
+        // The references are resolved after all labels are known, and the
+        // errors are reported at the chapters
+        let chapters = slides
+            .into_iter()
+            .map(|chapter| (chapter.slide.pos, chapter.to_html(ctx) + "\n"))
+            .collect::<Vec<_>>();
+        let mut s = String::new();
+        for (pos, html) in chapters {
+            ctx.pos.set(pos);
+            s += &resolve_refs(&html, ctx);
+        }
+        ctx.pos.set(None);
+        if let Some(bib) = &ctx.bib {
+            s += &bib.references(ctx);
+        }
+        s
     }
 }
 
 /// The chapter slide.
 ///
 /// Please see [`Slides`] for more information.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct ChapterSlide {
     /// Chapter slides have all attributes of other slides. (*flatten*)
@@ -113,25 +153,55 @@ pub struct ChapterSlide {
     pub sub: Vec<Slide>,
 }
 
+impl ChapterSlide {
+    /// Record the source positions of the chapter and its sections.
+    pub(crate) fn set_pos(&mut self, n: &NodeRc) {
+        self.slide.pos = Some(n.pos());
+        if let Ok(sub) = n.get("sub").and_then(NodeRc::as_seq) {
+            for (slide, n) in self.sub.iter_mut().zip(sub) {
+                slide.pos = Some(n.pos());
+            }
+        }
+    }
+}
+
 impl ToHtml for ChapterSlide {
     fn to_html(self, ctx: &Ctx) -> String {
         let Self { slide, sub } = self;
         if let Some(header) = &ctx.chapter_header {
             header.borrow_mut().clear();
         }
+        let h = ctx.chapter.get();
+        ctx.chapter.set(h + 1);
+        let anchor = |id: &str, v: usize| {
+            ctx.anchor.replace(match (id, v) {
+                ("", 0) => h.to_string(),
+                ("", v) => format!("{h}/{v}"),
+                (id, _) => id.to_string(),
+            });
+        };
         let title = slide_title(&slide).to_string();
+        anchor(&slide.id, 0);
         let slide = slide.to_html(ctx);
         if let Some(header) = &ctx.chapter_header {
             header.replace(title);
         }
-        format!("<section>\n{slide}{}</section>", sub.to_html(ctx))
+        let sub = sub
+            .into_iter()
+            .enumerate()
+            .map(|(v, slide)| {
+                anchor(&slide.id, v + 1);
+                slide.to_html(ctx) + "\n"
+            })
+            .collect::<String>();
+        format!("<section>\n{slide}{sub}</section>")
     }
 }
 
 /// All slides has following attributes.
 ///
 /// Please see [`Slides`] for more information.
-#[derive(Default, serde::Deserialize)]
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default)]
 pub struct Slide {
     /// Markdown level 1 title without `#` notation.
@@ -152,7 +222,8 @@ pub struct Slide {
     /// Background setting, as same as global.
     ///
     /// + Local background option can be boolean `false` to disable global
-    /// background.
+    ///   background.
+    #[schemars(with = "schema::Optional<Background>")]
     pub background: Optional<Background>,
     /// HTML "class" attribute for this section.
     ///
@@ -161,6 +232,8 @@ pub struct Slide {
     /// HTML "id" attribute for this section.
     ///
     /// For example, "my-id" will become `<section id="my-id">`.
+    ///
+    /// Defaults to the slug of the title. The ids must be unique.
     pub id: String,
     /// HTML tag attributes, separated by space.
     ///
@@ -174,6 +247,9 @@ pub struct Slide {
     /// [Background transition](https://revealjs.com/transitions/#background-transitions) option.
     #[serde(rename = "bg-trans")]
     pub bg_trans: String,
+    /// Source position of the slide, for the diagnostics.
+    #[serde(skip)]
+    pub pos: Option<u64>,
 }
 
 impl ToHtml for Slide {
@@ -191,7 +267,9 @@ impl ToHtml for Slide {
             auto_animate,
             trans,
             bg_trans,
+            pos,
         } = self;
+        ctx.pos.set(pos);
         let background = match background {
             Optional::Bool(false) => String::new(),
             Optional::Bool(true) => ctx.background.clone(),
@@ -209,16 +287,18 @@ impl ToHtml for Slide {
             + &bg_trans.wrap(" data-background-transition=\"", "\"")
             + auto_animate
             + &attr.wrap(" ", "");
-        let content = md2html(&title.wrap("# ", ""))
-            + &md2html(&title_only.wrap("# ", ""))
-            + &content.to_html(ctx)
-            + &md2html(&note).wrap("<aside class=\"notes\">", "</aside>\n");
+        let content = md2html(&title.wrap("# ", ""), ctx)
+            + &md2html(&title_only.wrap("# ", ""), ctx)
+            + &content.to_html(ctx);
+        let note = md2html(&note, ctx).wrap("<aside class=\"notes\">", "</aside>\n");
+        // The citations of the note are also listed
+        let footnote = ctx.bib.as_ref().map(Bib::footnote).unwrap_or_default();
         let header = ctx
             .chapter_header
             .as_ref()
             .map(|s| s.borrow().wrap("<div class=\"chapter-header\">", "</div>"))
             .unwrap_or_default();
         ctx.frag.set(0);
-        format!("<section{data}>\n{content}{header}</section>")
+        format!("<section{data}>\n{content}{footnote}{note}{header}</section>")
     }
 }
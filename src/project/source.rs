@@ -0,0 +1,213 @@
+use super::{Diagnostic, Diagnostics};
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+use yaml_peg::{parse, parser::PError, NodeRc, Yaml};
+
+const INCLUDE: &str = "tag:yaml.org,2002:include";
+
+/// The source files of a project.
+///
+/// Each file is placed at a global offset, so the node positions of different
+/// files will not be confused.
+#[derive(Default)]
+pub(crate) struct Sources {
+    files: Vec<(PathBuf, String, u64)>,
+    /// The included files which could not be read.
+    missing: Vec<PathBuf>,
+    stack: Vec<PathBuf>,
+}
+
+impl Sources {
+    /// Parse a document and resolve its `!!include` nodes.
+    pub(crate) fn parse(&mut self, path: &Path, doc: &str) -> Result<Vec<NodeRc>, Diagnostics> {
+        let offset = match self.files.last() {
+            Some((_, doc, offset)) => offset + doc.len() as u64 + 1,
+            None => 0,
+        };
+        self.files
+            .push((path.to_path_buf(), doc.to_string(), offset));
+        let yaml = parse(doc).map_err(|e| match e {
+            // The message starts with the "line:column" indicator
+            PError::Terminate { name, msg } => {
+                let (line, column) = msg
+                    .lines()
+                    .next()
+                    .and_then(|s| s.split_once(':'))
+                    .and_then(|(l, c)| Some((l.parse::<usize>().ok()?, c.parse::<u64>().ok()?)))
+                    .unwrap_or((1, 1));
+                let pos = doc
+                    .split_inclusive('\n')
+                    .take(line - 1)
+                    .map(|s| s.len() as u64)
+                    .sum::<u64>()
+                    + column
+                    - 1;
+                Diagnostic::at(format!("invalid {name}"), offset + pos)
+            }
+            PError::Mismatch => Diagnostic::at("invalid YAML document", offset),
+        })?;
+        self.stack.push(path.to_path_buf());
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut nodes = Vec::new();
+        for n in yaml {
+            self.splice(&n, base, offset, &mut nodes)?;
+        }
+        self.stack.pop();
+        Ok(nodes)
+    }
+
+    /// Resolve the source location of the diagnostic from its position.
+    pub(crate) fn locate(&self, mut d: Diagnostic) -> Diagnostic {
+        let Some(pos) = d.pos else {
+            return d;
+        };
+        let Some((path, doc, offset)) = self
+            .files
+            .iter()
+            .rev()
+            .find(|(_, _, offset)| *offset <= pos)
+        else {
+            return d;
+        };
+        let mut i = ((pos - offset) as usize).min(doc.len());
+        while !doc.is_char_boundary(i) {
+            i -= 1;
+        }
+        let start = doc[..i].rfind('\n').map_or(0, |j| j + 1);
+        d.file = path.display().to_string();
+        d.line = doc[..i].matches('\n').count() + 1;
+        d.column = doc[start..i].chars().count() + 1;
+        d.excerpt = doc[start..].lines().next().unwrap_or_default().to_string();
+        d
+    }
+
+    /// The global position of a byte offset in the value of a scalar node.
+    ///
+    /// The block scalars start from the next line, and their lines are
+    /// assumed to have the same indentation.
+    pub(crate) fn scalar_pos(&self, pos: u64, value: &str, offset: usize) -> u64 {
+        let Some((_, doc, base)) = self.files.iter().rev().find(|(_, _, o)| *o <= pos) else {
+            return pos;
+        };
+        let start = (pos - base) as usize;
+        let Some(src) = doc.get(start..) else {
+            return pos;
+        };
+        let head = &value[..offset.min(value.len())];
+        let line = head.matches('\n').count();
+        let column = head.len() - head.rfind('\n').map_or(0, |i| i + 1);
+        let block = src.starts_with(['|', '>']);
+        if !block && line == 0 {
+            let quote = usize::from(src.starts_with(['"', '\'']));
+            return pos + (quote + column) as u64;
+        }
+        let lines = src.split_inclusive('\n').collect::<Vec<_>>();
+        let indent = |s: &str| s.len() - s.trim_start_matches(' ').len();
+        let (skip, indent) = if block {
+            let first = lines[1..].iter().find(|s| !s.trim().is_empty());
+            (line + 1, first.map_or(0, |s| indent(s)))
+        } else {
+            (line, lines.get(line).map_or(0, |s| indent(s)))
+        };
+        let i = lines.iter().take(skip).map(|s| s.len()).sum::<usize>();
+        pos + (i + indent + column) as u64
+    }
+
+    /// Resolve the source locations of the diagnostics.
+    pub(crate) fn diagnostics(&self, diags: Vec<Diagnostic>) -> Diagnostics {
+        Diagnostics(diags.into_iter().map(|d| self.locate(d)).collect())
+    }
+
+    /// All of the loaded files, including the missing included files.
+    pub(crate) fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .map(|(path, _, _)| path)
+            .chain(&self.missing)
+            .map(PathBuf::as_path)
+    }
+
+    fn include(
+        &mut self,
+        n: &NodeRc,
+        base: &Path,
+        offset: u64,
+    ) -> Result<Vec<NodeRc>, Diagnostics> {
+        let pos = n.pos() + offset;
+        let path = match n.as_str() {
+            Ok(path) if !path.is_empty() => base.join(path),
+            _ => {
+                let msg = "`!!include` requires a file path";
+                return Err(Diagnostic::at(msg, pos).into());
+            }
+        };
+        if self.stack.contains(&path) {
+            let msg = format!("recursive include of {}", path.display());
+            return Err(Diagnostic::at(msg, pos).into());
+        }
+        let doc = match read_to_string(&path) {
+            Ok(doc) => doc,
+            Err(e) => {
+                let d = Diagnostic::at(format!("{}: {e}", path.display()), pos);
+                // Watch the missing file, it may be created later
+                self.missing.push(path);
+                return Err(d.into());
+            }
+        };
+        self.parse(&path, &doc)
+    }
+
+    /// Included sequences and documents are flatten into the parent sequence.
+    fn splice(
+        &mut self,
+        n: &NodeRc,
+        base: &Path,
+        offset: u64,
+        seq: &mut Vec<NodeRc>,
+    ) -> Result<(), Diagnostics> {
+        if n.tag() != INCLUDE {
+            seq.push(self.resolve(n, base, offset)?);
+            return Ok(());
+        }
+        for n in self.include(n, base, offset)? {
+            match n.yaml() {
+                Yaml::Seq(s) => seq.extend(s.iter().cloned()),
+                _ => seq.push(n),
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve(&mut self, n: &NodeRc, base: &Path, offset: u64) -> Result<NodeRc, Diagnostics> {
+        let yaml = match n.yaml() {
+            Yaml::Seq(s) => {
+                let mut seq = Vec::with_capacity(s.len());
+                for n in s {
+                    self.splice(n, base, offset, &mut seq)?;
+                }
+                Yaml::Seq(seq)
+            }
+            Yaml::Map(m) => {
+                let mut map = Vec::with_capacity(m.len());
+                for (k, v) in m {
+                    let v = if v.tag() == INCLUDE {
+                        let mut docs = self.include(v, base, offset)?;
+                        if docs.len() == 1 {
+                            docs.remove(0)
+                        } else {
+                            NodeRc::new(Yaml::Seq(docs), v.pos() + offset, "")
+                        }
+                    } else {
+                        self.resolve(v, base, offset)?
+                    };
+                    map.push((self.resolve(k, base, offset)?, v));
+                }
+                map.into_iter().collect()
+            }
+            yaml => yaml.clone(),
+        };
+        Ok(NodeRc::new(yaml, n.pos() + offset, n.tag()))
+    }
+}
@@ -0,0 +1,95 @@
+use super::*;
+use std::collections::HashMap;
+
+/// A slide of the document outline.
+pub(crate) struct Symbol {
+    /// The title, or the id if the title is empty.
+    pub(crate) name: String,
+    /// The slide id, generated from the title if not given.
+    pub(crate) id: String,
+    /// The location of the slide.
+    pub(crate) loc: Diagnostic,
+    /// The section slides of a chapter.
+    pub(crate) sub: Vec<Symbol>,
+}
+
+/// The slide locations of a project.
+pub(crate) struct Symbols {
+    /// The chapter slides in the source order.
+    pub(crate) slides: Vec<Symbol>,
+    /// The locations of the slide ids and the `h/v` anchors.
+    links: HashMap<String, Diagnostic>,
+}
+
+impl Symbols {
+    /// Load the project without rendering, and locate the slides.
+    pub(crate) fn new(doc: &str, path: &Path) -> Result<Self, Diagnostics> {
+        let Project { sources, metadata, mut slides, .. } =
+            Project::parse(doc, path, false, &mut Vec::new())?;
+        assign_ids(&mut slides, &Ctx::default());
+        let loc = |slide: &Slide| slide.pos.map(|pos| sources.locate(Diagnostic::at("", pos)));
+        let symbol = |slide: &Slide, sub| {
+            let name = match slide_title(slide) {
+                "" if slide.id.is_empty() => "(untitled)",
+                "" => &slide.id,
+                title => title,
+            };
+            Some(Symbol {
+                name: name.to_string(),
+                id: slide.id.clone(),
+                loc: loc(slide)?,
+                sub,
+            })
+        };
+        let symbols = slides
+            .iter()
+            .filter_map(|c| {
+                let sub = c.sub.iter().filter_map(|s| symbol(s, Vec::new())).collect();
+                symbol(&c.slide, sub)
+            })
+            .collect();
+        // The anchors count the generated slides
+        if metadata.title_slide != TitleLayout::None {
+            slides.insert(0, ChapterSlide::default());
+        }
+        if let Some(outline) = OutlineOption::new(metadata.outline) {
+            outline.apply(&mut slides);
+        }
+        let mut links = HashMap::new();
+        for (h, chapter) in slides.iter().enumerate() {
+            for (v, slide) in std::iter::once(&chapter.slide)
+                .chain(&chapter.sub)
+                .enumerate()
+            {
+                let Some(loc) = loc(slide) else {
+                    continue;
+                };
+                if !slide.id.is_empty() {
+                    links.entry(slide.id.clone()).or_insert_with(|| loc.clone());
+                }
+                let anchor = if v == 0 {
+                    h.to_string()
+                } else {
+                    format!("{h}/{v}")
+                };
+                links.insert(anchor, loc);
+            }
+        }
+        Ok(Self { slides: symbols, links })
+    }
+
+    /// The slide ids and the slide names.
+    pub(crate) fn ids(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.slides
+            .iter()
+            .flat_map(|s| std::iter::once(s).chain(&s.sub))
+            .filter(|s| !s.id.is_empty())
+            .map(|s| (s.id.as_str(), s.name.as_str()))
+    }
+
+    /// The location of a slide link, an id or a `h/v` anchor.
+    pub(crate) fn find(&self, link: &str) -> Option<&Diagnostic> {
+        let link = link.strip_suffix("/0").unwrap_or(link);
+        self.links.get(link)
+    }
+}
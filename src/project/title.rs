@@ -0,0 +1,198 @@
+use super::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+use yaml_peg::serde::InlineList;
+
+/// The layout of the generated title slide.
+#[derive(Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleLayout {
+    /// No title slide, the default option.
+    #[default]
+    None,
+    /// Centered texts, the logos are placed at the bottom.
+    Center,
+    /// Left-aligned texts, the logos are placed at the bottom.
+    Left,
+    /// Centered texts, the logos are placed at the top.
+    Banner,
+}
+
+/// An author, a name or a map.
+///
+/// ```yaml
+/// authors:
+///   - Alice
+///   - name: Bob
+///     affiliation: Example University
+///     email: bob@example.com
+/// ```
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum Author {
+    /// The name only.
+    Name(String),
+    /// The author information.
+    Info(AuthorInfo),
+}
+
+/// The author information.
+#[derive(Default, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct AuthorInfo {
+    /// Author name.
+    pub name: String,
+    /// Affiliation, the same affiliations are merged.
+    pub affiliation: String,
+    /// Email address, linked on the name.
+    pub email: String,
+}
+
+impl Author {
+    /// The author name.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Name(name) => name,
+            Self::Info(info) => &info.name,
+        }
+    }
+}
+
+/// Today's date in UTC, "YYYY-MM-DD" format.
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    // Civil date from the days since 1970-01-01
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// The information of the generated title slide.
+pub(crate) struct TitleSlide<'a> {
+    pub(crate) layout: TitleLayout,
+    pub(crate) title: &'a str,
+    pub(crate) subtitle: &'a str,
+    pub(crate) authors: &'a [Author],
+    pub(crate) date: &'a str,
+    pub(crate) event: &'a str,
+    pub(crate) logo: InlineList<Sized>,
+}
+
+impl TitleSlide<'_> {
+    /// Generate the title slide if the layout is not `none`.
+    pub(crate) fn into_slide(self) -> Option<ChapterSlide> {
+        let Self {
+            layout,
+            title,
+            subtitle,
+            authors,
+            date,
+            event,
+            logo,
+        } = self;
+        let class = match layout {
+            TitleLayout::None => return None,
+            TitleLayout::Center => "title-slide",
+            TitleLayout::Left => "title-slide title-left",
+            TitleLayout::Banner => "title-slide title-banner",
+        };
+        let mut affiliations = Vec::<&str>::new();
+        for author in authors {
+            if let Author::Info(AuthorInfo { affiliation, .. }) = author {
+                if !affiliation.is_empty() && !affiliations.contains(&affiliation.as_str()) {
+                    affiliations.push(affiliation);
+                }
+            }
+        }
+        let names = authors
+            .iter()
+            .map(|author| {
+                let (name, affiliation, email) = match author {
+                    Author::Name(name) => (name.as_str(), "", ""),
+                    Author::Info(info) => (info.name.as_str(), &*info.affiliation, &*info.email),
+                };
+                let name = html_escape(name);
+                let mut s = if email.is_empty() {
+                    name
+                } else {
+                    format!("<a href=\"mailto:{}\">{name}</a>", html_escape(email))
+                };
+                if affiliations.len() > 1 {
+                    if let Some(i) = affiliations.iter().position(|a| *a == affiliation) {
+                        s += &format!("<sup>{}</sup>", i + 1);
+                    }
+                }
+                s
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+            .wrap("<p>", "</p>");
+        let affiliations = if affiliations.len() > 1 {
+            affiliations
+                .iter()
+                .enumerate()
+                .map(|(i, a)| format!("<sup>{}</sup>{}", i + 1, html_escape(a)))
+                .collect::<Vec<_>>()
+                .join("<br/>")
+        } else {
+            html_escape(&affiliations.concat())
+        }
+        .wrap("<p>", "</p>");
+        let date = if date == "today" {
+            today()
+        } else {
+            date.to_string()
+        };
+        let footer = [event, &date]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" · ");
+        // The title is placed in the document, so the banner logos can be
+        // placed above it, the authors are written in HTML to be escaped
+        let doc = [
+            title.wrap("# ", ""),
+            subtitle.wrap("## ", ""),
+            names,
+            affiliations,
+            footer,
+        ]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+        let img = InlineList::List(
+            logo.into_iter()
+                .map(|size| Img { size, ..Default::default() })
+                .collect(),
+        );
+        let blocks = if layout == TitleLayout::Banner {
+            vec![
+                Content { img, ..Default::default() },
+                Content { doc, ..Default::default() },
+            ]
+        } else {
+            vec![
+                Content { doc, ..Default::default() },
+                Content { img, ..Default::default() },
+            ]
+        };
+        let slide = Slide {
+            title_hidden: title.to_string(),
+            id: "title".to_string(),
+            class: class.to_string(),
+            content: Content { blocks, ..Default::default() },
+            ..Default::default()
+        };
+        Some(ChapterSlide { slide, sub: Vec::new() })
+    }
+}
@@ -1,5 +1,8 @@
+use super::{Bib, Diagnostic, MarkdownOption, MathOption, OutlineOption, Syntect};
 use std::{
     cell::{Cell, RefCell},
+    collections::HashMap,
+    path::{Path, PathBuf},
     rc::Rc,
 };
 
@@ -7,7 +10,7 @@ use std::{
 #[derive(Default)]
 pub struct Ctx {
     /// Outline option.
-    pub outline: String,
+    pub outline: Option<OutlineOption>,
     /// Global auto-animate option.
     pub auto_animate: bool,
     /// Global chapter-header option.
@@ -15,7 +18,58 @@ pub struct Ctx {
     /// Background setting (in HTML).
     pub background: String,
     /// Fragment counter.
-    pub frag: Rc<Cell<u8>>,
+    pub frag: Rc<Cell<u32>>,
+    /// Math option.
+    pub math: MathOption,
+    /// Markdown extensions.
+    pub markdown: MarkdownOption,
+    /// Equation counter.
+    pub eq: Cell<usize>,
+    /// Figure counter.
+    pub fig: Cell<usize>,
+    /// Table counter.
+    pub tab: Cell<usize>,
+    /// Chapter counter.
+    pub chapter: Cell<usize>,
+    /// The anchor of the current slide, the id or the `h/v` indices.
+    pub anchor: RefCell<String>,
+    /// The titles of the slide ids.
+    pub slides: RefCell<HashMap<String, String>>,
+    /// Cross-reference labels, the reference text and the slide anchor.
+    pub refs: RefCell<HashMap<String, (String, String)>>,
+    /// Build-time highlighter.
+    pub syntect: Option<Syntect>,
+    /// Bibliography for the citations.
+    pub bib: Option<Bib>,
+    /// Source position of the current slide.
+    pub pos: Cell<Option<u64>>,
+    /// Errors during the conversion.
+    pub errors: RefCell<Vec<Diagnostic>>,
+    /// The files that are read during the conversion.
+    pub files: RefCell<Vec<PathBuf>>,
+    /// The project directory, the local files are read from it.
+    pub base: PathBuf,
+}
+
+impl Ctx {
+    /// Report an error at the current slide.
+    pub fn error(&self, msg: impl ToString) {
+        let d = Diagnostic { pos: self.pos.get(), ..Diagnostic::new(msg) };
+        self.errors.borrow_mut().push(d);
+    }
+
+    /// Read a local file and record it. Report an error if failed.
+    pub fn read<P>(&self, path: P) -> Option<String>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let full = self.base.join(path);
+        self.files.borrow_mut().push(full.clone());
+        std::fs::read_to_string(full)
+            .map_err(|e| self.error(format!("{}: {e}", path.display())))
+            .ok()
+    }
 }
 
 /// Let data structure transform to HTML.
@@ -0,0 +1,265 @@
+use super::*;
+use crate::update::archive;
+use binstall_zip::ZipArchive;
+use std::collections::HashSet;
+use yaml_peg::{serde::Optional, Yaml};
+
+/// The slide attributes, a slide with these keys only is empty.
+const ATTRS: &[&str] = &[
+    "id",
+    "class",
+    "attr",
+    "trans",
+    "bg-trans",
+    "auto-animate",
+    "note",
+    "sub",
+];
+/// The metadata keys of the file paths.
+const META_FILES: &[&str] = &["icon", "bibliography", "template", "syntaxes"];
+
+/// The file names of the Reveal.js archive, without the root folder.
+pub(crate) fn archive_files() -> Option<HashSet<String>> {
+    let path = std::env::current_exe()
+        .ok()?
+        .with_file_name(concat!(archive!(), ".zip"));
+    let zip = ZipArchive::new(std::fs::File::open(path).ok()?).ok()?;
+    let files = zip
+        .file_names()
+        .filter_map(|name| name.strip_prefix(concat!(archive!(), "/")))
+        .map(str::to_string)
+        .collect();
+    Some(files)
+}
+
+/// The slide ids of the `#/id` links in the text, the numbered anchors are
+/// skipped. The `[[id]]` links are checked by the rendering.
+fn links(text: &str) -> Vec<&str> {
+    let mut ids = Vec::new();
+    for (i, _) in text.match_indices("#/") {
+        if !text[..i].ends_with(['(', '"', '\'']) {
+            continue;
+        }
+        let anchor = &text[i + 2..];
+        let end = anchor
+            .find([')', '"', '\'', '/', ' ', '\n'])
+            .unwrap_or(anchor.len());
+        ids.push(&anchor[..end]);
+    }
+    ids.retain(|id| !id.chars().all(|c| c.is_ascii_digit()));
+    ids
+}
+
+fn is_empty(n: &NodeRc) -> bool {
+    let Ok(map) = n.as_map() else {
+        return false;
+    };
+    map.iter().all(|(k, v)| {
+        ATTRS.contains(&k.as_str().unwrap_or_default()) || v.is_null() || v.as_str() == Ok("")
+    })
+}
+
+struct Validator {
+    archive: HashSet<String>,
+    ids: HashSet<String>,
+    /// The project directory.
+    base: PathBuf,
+    /// The missing files, which are also reported by the rendering.
+    missing: HashSet<String>,
+    diags: Vec<Diagnostic>,
+}
+
+impl Validator {
+    /// Check a local file, the URLs are skipped.
+    fn file(&mut self, n: &NodeRc) {
+        let Ok(src) = n.as_str() else {
+            return;
+        };
+        if src.is_empty()
+            || src.contains("://")
+            || src.starts_with("//")
+            || src.starts_with("data:")
+            || src.starts_with('#')
+        {
+            return;
+        }
+        let path = src.split(['?', '#']).next().unwrap_or_default();
+        let path = path.trim_start_matches('/');
+        if !self.base.join(path).exists() && !self.archive.contains(path) {
+            let msg = format!("missing file `{src}`");
+            self.diags.push(Diagnostic::at(msg, n.pos()));
+            self.missing.insert(path.to_string());
+        }
+    }
+
+    /// Check the `src` / `include` files and the slide links in the node.
+    fn walk(&mut self, n: &NodeRc) {
+        match n.yaml() {
+            Yaml::Map(map) => {
+                for (k, v) in map {
+                    if matches!(k.as_str(), Ok("src" | "include")) {
+                        self.file(v);
+                    }
+                    self.walk(v);
+                }
+            }
+            Yaml::Seq(seq) => seq.iter().for_each(|n| self.walk(n)),
+            Yaml::Str(text) => {
+                for id in links(text) {
+                    if !self.ids.contains(id) {
+                        let msg = format!("undefined slide link `#/{id}`");
+                        self.diags.push(Diagnostic::at(msg, n.pos()));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn metadata(&mut self, n: &NodeRc) {
+        for (k, v) in n.as_map().unwrap_or_default() {
+            match k.as_str().unwrap_or_default() {
+                key if META_FILES.contains(&key) => self.file(&v),
+                "plugin" => {
+                    for (_, files) in v.as_map().unwrap_or_default() {
+                        for f in files.as_seq().unwrap_or_default() {
+                            self.file(&f);
+                        }
+                    }
+                }
+                "templates" | "vars" => (),
+                _ => self.walk(&v),
+            }
+        }
+    }
+
+    fn slide(&mut self, n: &NodeRc) {
+        if is_empty(n) {
+            let d = Diagnostic::at("empty slide", n.pos());
+            self.diags.push(Diagnostic { warning: true, ..d });
+        }
+    }
+
+    /// Check the themes in the archive.
+    fn themes(&mut self, meta: Option<&NodeRc>, metadata: &Metadata) {
+        let pos = |key: &str| meta.and_then(|n| n.get(key).ok()).map(NodeRc::pos);
+        let available = |dir: &str| {
+            let mut names = self
+                .archive
+                .iter()
+                .filter_map(|f| f.strip_prefix(dir)?.strip_suffix(".css"))
+                .collect::<Vec<_>>();
+            names.sort_unstable();
+            format!("available themes: {}", names.join(", "))
+        };
+        let mut themes = vec![("theme", "dist/theme/", &metadata.theme)];
+        match metadata.highlighter {
+            CodeHighlighter::HighlightJs => {
+                themes.push(("code-theme", "plugin/highlight/", &metadata.code_theme));
+            }
+            CodeHighlighter::Syntect => {
+                if let Err(e) = Syntect::new(&metadata.code_theme, &metadata.syntaxes, &self.base) {
+                    let d = Diagnostic { pos: pos("code-theme"), ..Diagnostic::new(e) };
+                    self.diags.push(d);
+                }
+            }
+        }
+        for (key, dir, theme) in themes {
+            if !self.archive.contains(&format!("{dir}{theme}.css")) {
+                let d = Diagnostic {
+                    pos: pos(key),
+                    hint: available(dir),
+                    ..Diagnostic::new(format!("unknown {key} `{theme}`"))
+                };
+                self.diags.push(d);
+            }
+        }
+    }
+}
+
+/// Check the project, the page is rendered but not written.
+///
+/// The missing files, the duplicated slide ids, the undefined slide links and
+/// the unknown themes are errors, the empty slides are warnings. The errors of
+/// the rendering are also reported, such as the references, the citations
+/// and the invalid TeX. The loaded files are collected.
+pub(crate) fn validate(
+    doc: &str,
+    path: &Path,
+    strict: bool,
+    files: &mut Vec<PathBuf>,
+) -> Diagnostics {
+    let Project {
+        sources,
+        meta,
+        metadata,
+        mut slides,
+        nodes,
+        warnings,
+    } = match Project::parse(doc, path, strict, files) {
+        Ok(project) => project,
+        Err(diags) => return diags,
+    };
+    let ctx = Ctx::default();
+    assign_ids(&mut slides, &ctx);
+    let mut ids = ctx.slides.take().into_keys().collect::<HashSet<_>>();
+    let reserved = [
+        ("title", metadata.title_slide != TitleLayout::None),
+        ("references", !metadata.bibliography.is_empty()),
+        (
+            "outline",
+            !matches!(metadata.outline, Optional::Bool(false)),
+        ),
+    ];
+    ids.extend(
+        reserved
+            .into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(id, _)| id.to_string()),
+    );
+    let mut diags = warnings;
+    diags.extend(ctx.errors.take());
+    let archive = archive_files();
+    if archive.is_none() {
+        let d = Diagnostic::new("Reveal.js archive not found, the themes are not checked")
+            .hint("run `rym update` to download the archive");
+        diags.push(Diagnostic { warning: true, ..d });
+    }
+    let mut v = Validator {
+        archive: archive.clone().unwrap_or_default(),
+        ids,
+        base: path.parent().unwrap_or(Path::new("")).to_path_buf(),
+        missing: HashSet::new(),
+        diags,
+    };
+    if let Some(n) = &meta {
+        v.metadata(n);
+    }
+    for n in &nodes {
+        v.slide(n);
+        for n in n.get("sub").and_then(NodeRc::as_seq).unwrap_or_default() {
+            v.slide(&n);
+        }
+        v.walk(n);
+    }
+    if archive.is_some() {
+        v.themes(meta.as_ref(), &metadata);
+    }
+    let mut diags = sources.diagnostics(v.diags);
+    let (Ok((_, rendered)) | Err(rendered)) =
+        load_inner(doc, path, "", false, false, strict, &mut Vec::new());
+    for d in rendered.0 {
+        let read = d
+            .msg
+            .split_once(": ")
+            .is_some_and(|(file, _)| v.missing.contains(file));
+        let found = diags
+            .0
+            .iter()
+            .any(|e| (&e.msg, &e.file, e.line, e.column) == (&d.msg, &d.file, d.line, d.column));
+        if !read && !found {
+            diags.0.push(d);
+        }
+    }
+    diags
+}
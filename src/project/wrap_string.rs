@@ -1,8 +1,18 @@
+use pulldown_cmark_escape::escape_html;
+
+/// Escape the text for HTML, unlike [`StringWrap::escape`] which is for the
+/// JavaScript strings.
+pub(crate) fn html_escape(s: &str) -> String {
+    let mut text = String::new();
+    escape_html(&mut text, s).unwrap();
+    text
+}
+
 /// Some string operations.
 pub trait StringWrap {
     /// Wrap string with prefix and suffix.
     fn wrap(&self, prefix: &str, suffix: &str) -> String;
-    /// HTML escaping.
+    /// Escape the newlines and the double quotes for the JavaScript strings.
     fn escape(&self) -> String;
 }
 
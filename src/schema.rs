@@ -0,0 +1,11 @@
+use crate::project::json_schema;
+use std::io::Result;
+
+/// Print the JSON schema of the project files.
+///
+/// The schema can be used by the YAML-aware editors for the completion and
+/// the validation.
+pub fn schema() -> Result<()> {
+    println!("{}", json_schema());
+    Ok(())
+}
@@ -1,15 +1,17 @@
 use self::edit_mode::Monitor;
 use crate::{
+    blank::ROOT,
     pack::{extract, listdir},
-    project::{error_page, load, single_page},
+    project::{error_page, load, load_doc, single_page, with_overlay},
     update::archive,
 };
 use actix_files::Files;
 use actix_web::{web, App, HttpServer};
 use std::{
-    fs::{canonicalize, read_to_string},
+    fs::canonicalize,
     io::{Error, ErrorKind, Result},
-    path::Path,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 use temp_dir::TempDir;
 
@@ -23,18 +25,35 @@ struct Cache {
     project: String,
     doc: String,
     help_doc: String,
+    /// The last successful page in edit mode, shown under the error overlay.
+    last: Arc<Mutex<String>>,
+    /// The files of the last loading in edit mode, watched by the monitor.
+    files: Arc<Mutex<Vec<PathBuf>>>,
     reload: bool,
+    offline: bool,
+    strict: bool,
 }
 
 /// Launch function.
-pub fn serve<P>(port: u16, path: P, project: String, edit: bool, open: bool) -> Result<()>
+///
+/// The offline mode serves MathJax from the archive. The strict mode reports
+/// the unknown keys as warnings.
+pub fn serve<P>(
+    port: u16,
+    path: P,
+    project: String,
+    edit: bool,
+    open: bool,
+    offline: bool,
+    strict: bool,
+) -> Result<()>
 where
     P: AsRef<Path>,
 {
     std::env::set_current_dir(path.as_ref())?;
     let temp = TempDir::new().map_err(|s| Error::new(ErrorKind::PermissionDenied, s))?;
     // Expand Reveal.js
-    extract(temp.path())?;
+    extract(temp.path(), offline)?;
     // Start server
     let archive = temp.path().join(archive!());
     println!("Serve at: http://localhost:{port}/");
@@ -42,22 +61,36 @@ where
     let full_path = canonicalize(".")?;
     println!("Local assets at: {full_path:?}");
     println!("Edit mode: {edit}");
+    println!("Offline mode: {offline}");
     println!("Press Ctrl+C to close the server...");
     let assets = listdir(".")?;
     let cache = web::Data::new(Cache {
         doc: if edit {
             String::new()
         } else {
-            load(&read_to_string(&project)?, "/static/", edit).unwrap_or_else(error_page)
+            load(&project, "/static/", edit, offline, strict, &mut Vec::new())
+                .unwrap_or_else(error_page)
         },
         project,
-        help_doc: load(HELP_DOC, "/static/", false)?,
+        help_doc: load_doc(
+            HELP_DOC,
+            Path::new(ROOT),
+            "/static/",
+            false,
+            offline,
+            false,
+            &mut Vec::new(),
+        )?,
+        last: Default::default(),
+        files: Default::default(),
         reload: edit,
+        offline,
+        strict,
     });
     let server = HttpServer::new(move || {
         let app = App::new()
             .app_data(cache.clone())
-            .app_data(web::Data::new(Monitor::new(cache.project.clone())))
+            .app_data(web::Data::new(Monitor::new(cache.files.clone())))
             .service(site::index)
             .service(site::help_page)
             .default_service(web::route().to(site::not_found))
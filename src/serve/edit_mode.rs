@@ -1,10 +1,15 @@
 use actix::{Actor, Addr, AsyncContext, Context, Handler, Message, StreamHandler};
 use actix_web::{get, web, Error, HttpRequest, HttpResponse};
 use actix_web_actors::ws;
-use std::time::{Duration, SystemTime};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
 
-fn file_date(path: &str) -> SystemTime {
-    std::fs::metadata(path).unwrap().modified().unwrap()
+fn file_date(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
 }
 
 struct Ws;
@@ -26,19 +31,15 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for Ws {
 }
 
 pub(super) struct Monitor {
-    last: SystemTime,
-    project: String,
+    last: HashMap<PathBuf, Option<SystemTime>>,
+    /// The files of the last loading, the missing files are included.
+    files: Arc<Mutex<Vec<PathBuf>>>,
     listeners: Vec<Addr<Ws>>,
 }
 
 impl Monitor {
-    pub(super) fn new(project: String) -> Addr<Self> {
-        Self {
-            last: file_date(&project),
-            project,
-            listeners: vec![],
-        }
-        .start()
+    pub(super) fn new(files: Arc<Mutex<Vec<PathBuf>>>) -> Addr<Self> {
+        Self { last: HashMap::new(), files, listeners: vec![] }.start()
     }
 }
 
@@ -47,13 +48,23 @@ impl Actor for Monitor {
 
     fn started(&mut self, ctx: &mut Self::Context) {
         ctx.run_interval(Duration::from_millis(500), |act, _| {
-            let last = file_date(&act.project);
-            if last != act.last {
+            // The newly loaded files are recorded without the broadcasting
+            let files = act.files.lock().unwrap().clone();
+            let mut changed = false;
+            let last = files
+                .into_iter()
+                .map(|path| {
+                    let date = file_date(&path);
+                    changed |= act.last.get(&path).is_some_and(|d| *d != date);
+                    (path, date)
+                })
+                .collect();
+            act.last = last;
+            if changed {
                 // Broadcast
                 for listener in &act.listeners {
                     listener.do_send(Event);
                 }
-                act.last = last;
             }
         });
     }
@@ -6,8 +6,28 @@ pub(super) async fn index(data: Data<Cache>) -> HttpResponse {
     HttpResponse::Ok()
         .content_type(ContentType::html())
         .body(if data.doc.is_empty() {
-            let doc = read_to_string(&data.project).unwrap_or_else(error_page);
-            load(&doc, "/static/", data.reload).unwrap_or_else(error_page)
+            let mut files = Vec::new();
+            let doc = load(
+                &data.project,
+                "/static/",
+                data.reload,
+                data.offline,
+                data.strict,
+                &mut files,
+            );
+            files.sort();
+            files.dedup();
+            *data.files.lock().unwrap() = files;
+            match doc {
+                Ok(doc) => {
+                    data.last.lock().unwrap().clone_from(&doc);
+                    doc
+                }
+                Err(diags) => match data.last.lock().unwrap().as_str() {
+                    "" => error_page(diags),
+                    last => with_overlay(last, &diags),
+                },
+            }
         } else {
             data.doc.clone()
         })
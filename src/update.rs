@@ -1,5 +1,5 @@
-use std::io::{Cursor, Result};
 use binstall_zip::{ZipArchive, ZipWriter};
+use std::io::{Cursor, Result};
 
 macro_rules! archive {
     () => {
@@ -13,19 +13,57 @@ macro_rules! reveal_url {
     };
 }
 
-pub(crate) use archive;
+macro_rules! mathjax {
+    () => {
+        "mathjax"
+    };
+}
 
-/// Download the archive from Reveal.js repository.
-pub fn update() -> Result<()> {
-    println!(concat!("Downloading archive from ", reveal_url!()));
-    let b = actix_web::rt::System::new().block_on(async {
-        reqwest::get(reveal_url!())
+macro_rules! mathjax_url {
+    () => {
+        "https://github.com/mathjax/MathJax/archive/refs/tags/2.7.9.zip"
+    };
+}
+
+pub(crate) use {archive, mathjax};
+
+/// The files of MathJax 2 that are required by the `TeX-AMS_HTML-full` config.
+const MATHJAX_FILES: &[&str] = &[
+    "MathJax.js",
+    "config/",
+    "extensions/",
+    "jax/",
+    "localization/",
+    "fonts/HTML-CSS/TeX/woff/",
+];
+
+fn download(url: &str) -> Vec<u8> {
+    println!("Downloading archive from {url}");
+    actix_web::rt::System::new().block_on(async {
+        reqwest::get(url)
             .await
             .unwrap()
             .bytes()
             .await
             .unwrap()
-    });
+            .to_vec()
+    })
+}
+
+/// Download the archive from Reveal.js repository, and the MathJax archive for
+/// the offline mode.
+pub fn update(offline: bool) -> Result<()> {
+    update_reveal()?;
+    if offline {
+        update_mathjax()?;
+    }
+    println!("Done");
+    Ok(())
+}
+
+/// Download the archive from Reveal.js repository.
+pub(crate) fn update_reveal() -> Result<()> {
+    let b = download(reveal_url!());
     let archive = std::env::current_exe()?.with_file_name(concat!(archive!(), ".zip"));
     let mut r = ZipArchive::new(Cursor::new(b))?;
     let mut w = ZipWriter::new(std::fs::File::create(archive)?);
@@ -42,6 +80,29 @@ pub fn update() -> Result<()> {
         }
     }
     w.finish()?;
-    println!("Done");
+    Ok(())
+}
+
+/// Download the MathJax archive, only used in the offline mode.
+pub(crate) fn update_mathjax() -> Result<()> {
+    let b = download(mathjax_url!());
+    let archive = std::env::current_exe()?.with_file_name(concat!(mathjax!(), ".zip"));
+    let mut r = ZipArchive::new(Cursor::new(b))?;
+    let mut w = ZipWriter::new(std::fs::File::create(archive)?);
+    for i in 0..r.len() {
+        let file = r.by_index(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        // Strip the root folder "MathJax-x.y.z/"
+        let name = match file.name().split_once('/') {
+            Some((_, name)) if MATHJAX_FILES.iter().any(|p| name.starts_with(p)) => {
+                format!(concat!(mathjax!(), "/{}"), name)
+            }
+            _ => continue,
+        };
+        w.raw_copy_file_rename(file, name)?;
+    }
+    w.finish()?;
     Ok(())
 }